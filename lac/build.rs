@@ -3,6 +3,17 @@ use std::env;
 fn main() {
     let sdk_stub = env::var("CARGO_FEATURE_SDK_STUB").is_ok();
 
+    // `chip_sdk` is set when a C chip SDK, real or stub, gets linked.
+    println!("cargo::rustc-check-cfg=cfg(chip_sdk)");
+    println!("cargo:rerun-if-env-changed=CHIP_SDK_LIB_DIR");
+    if let Ok(dir) = env::var("CHIP_SDK_LIB_DIR") {
+        println!("cargo:rustc-link-search=native={}", dir);
+        println!("cargo:rustc-link-lib=chip_sdk");
+        println!("cargo:rustc-cfg=chip_sdk");
+    } else if sdk_stub {
+        println!("cargo:rustc-cfg=chip_sdk");
+    }
+
     if sdk_stub {
         println!("cargo:rerun-if-changed=tests/c_stubs/");
 
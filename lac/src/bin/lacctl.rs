@@ -0,0 +1,772 @@
+//! Operator and test bench tool for link aggregation.
+//!
+//! Group and MAC settings live in a configuration file (`--config`, or
+//! `$LAC_CONFIG`), applied to the device on every run and saved back after
+//! each change.
+
+use lac::ffi::*;
+use lac::intf::LAC_CONFIG_ENV;
+use lac::lac::*;
+use lac::sdk::*;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+const USAGE: &str = "\
+Usage: lacctl [OPTIONS] <COMMAND>
+
+Commands:
+  chips                           List chips
+  ports                           List ports with speed, link, group and MAC
+  groups                          List groups and their state
+  group create <ID> [--hash <POLICY>] [--min-links <N>] [--speed <SPEED>]
+               [--mac-pool <PATH>]
+                                  Create a group, taking its MAC from a MAC pool file
+  group delete <ID>
+  group mac <ID> <MAC|none>       Set the MAC shared by all members
+  member add <ID> <PORT>
+  member remove <ID> <PORT>
+  mac set <PORT> <MAC|none>       Set the MAC of a port outside a MAC group
+  macs                            List programmed MACs
+  events [--count <N>] [--timeout <SECS>]
+                                  Print link events as they arrive
+
+Options:
+  --backend <sdk|stub|sim>        Chip SDK to drive [default: sdk if linked, else sim]
+  --topology <CHIPSxPORTS[@SPEED]>
+                                  Chips created by the stub and sim backends [default: 2x4@10G]
+  --config <PATH>                 Configuration file [default: $LAC_CONFIG or lacctl.toml]
+  --hold-up <MS>                  Time a port must stay up before it counts as up
+  --hold-down <MS>                Time a port must stay down before it counts as down
+  --dampen                        Suppress flapping ports with the default penalty policy
+  --fail <init|set_mac|set_link_status_handler>
+                                  Make every call of an SDK operation fail (sim backend only)
+  --dry-run                       Print the changes a command would make
+  --json                          Print JSON instead of tables
+  -h, --help                      Print this help
+";
+
+#[derive(Debug)]
+struct CliError(String);
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<ConfigError> for CliError {
+    fn from(err: ConfigError) -> Self {
+        CliError(err.to_string())
+    }
+}
+
+impl From<LagError> for CliError {
+    fn from(err: LagError) -> Self {
+        CliError(err.to_string())
+    }
+}
+
+impl From<MacPoolError> for CliError {
+    fn from(err: MacPoolError) -> Self {
+        CliError(err.to_string())
+    }
+}
+
+type CliResult<T> = Result<T, CliError>;
+
+macro_rules! fail {
+    ($($arg:tt)*) => {
+        return Err(CliError(format!($($arg)*)))
+    };
+}
+
+fn parse<T: FromStr>(what: &str, value: &str) -> CliResult<T>
+where
+    T::Err: fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|err| CliError(format!("Invalid {} '{}': {}", what, value, err)))
+}
+
+fn parse_mac(value: &str) -> CliResult<Option<MacAddr>> {
+    match value {
+        "none" => Ok(None),
+        _ => parse("MAC", value).map(Some),
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Backend {
+    Sdk,
+    Stub,
+    Sim,
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sdk" => Ok(Backend::Sdk),
+            "stub" => Ok(Backend::Stub),
+            "sim" => Ok(Backend::Sim),
+            _ => Err("expected sdk, stub or sim".to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Backend::Sdk => write!(f, "sdk"),
+            Backend::Stub => write!(f, "stub"),
+            Backend::Sim => write!(f, "sim"),
+        }
+    }
+}
+
+/// Chips the stub and sim backends start with.
+#[derive(Debug, Copy, Clone)]
+struct TopologySpec {
+    chips: usize,
+    ports: usize,
+    speed: PortSpeed,
+}
+
+impl Default for TopologySpec {
+    fn default() -> Self {
+        TopologySpec {
+            chips: 2,
+            ports: 4,
+            speed: PortSpeed::GBPS_10,
+        }
+    }
+}
+
+impl FromStr for TopologySpec {
+    type Err = String;
+
+    /// Parses `2x4` or `2x4@25G`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (layout, speed) = match s.split_once('@') {
+            Some((layout, speed)) => (layout, speed.parse().map_err(|err| format!("{}", err))?),
+            None => (s, PortSpeed::GBPS_10),
+        };
+        let (chips, ports) = layout
+            .split_once('x')
+            .and_then(|(chips, ports)| Some((chips.parse().ok()?, ports.parse().ok()?)))
+            .ok_or("expected CHIPSxPORTS")?;
+        if chips > CHIP_SDK_CHIP_MAX || ports > CHIP_SDK_PHY_PORT_PER_CHIP {
+            return Err(format!(
+                "at most {} chips of {} ports",
+                CHIP_SDK_CHIP_MAX, CHIP_SDK_PHY_PORT_PER_CHIP
+            ));
+        }
+        Ok(TopologySpec {
+            chips,
+            ports,
+            speed,
+        })
+    }
+}
+
+impl TopologySpec {
+    fn speed(&self) -> i32 {
+        i32::try_from(self.speed.mbps()).unwrap_or(i32::MAX)
+    }
+
+    /// Simulator with the chips of this topology, which the stub backend
+    /// copies too.
+    fn sim(&self) -> CliResult<SimSdk> {
+        SimSdk::with_topology(self.chips, self.ports, self.speed())
+            .map_err(|err| CliError(format!("Failed to build simulator: {}", err)))
+    }
+}
+
+#[cfg(feature = "sdk_stub")]
+extern "C" {
+    fn device_add_chip(chip: *const SwitchChip) -> ChipSdkError;
+}
+
+fn default_backend() -> Backend {
+    if cfg!(chip_sdk) {
+        Backend::Sdk
+    } else {
+        Backend::Sim
+    }
+}
+
+fn parse_op(value: &str) -> CliResult<SdkOp> {
+    match value {
+        "init" => Ok(SdkOp::Init),
+        "set_link_status_handler" => Ok(SdkOp::SetLinkStatusHandler),
+        "set_mac" => Ok(SdkOp::SetMac),
+        _ => fail!("Invalid SDK operation '{}'", value),
+    }
+}
+
+fn open_sdk(options: &Options) -> CliResult<Box<dyn ChipSdk>> {
+    let topology = &options.topology;
+    if !options.fail.is_empty() && options.backend != Backend::Sim {
+        fail!("--fail is only supported by the sim backend");
+    }
+    match options.backend {
+        Backend::Sim => {
+            let sdk = FaultySdk::new(topology.sim()?);
+            for op in &options.fail {
+                sdk.inject(Fault::new(*op, ChipSdkError::CHIP_SDK_ERROR));
+            }
+            Ok(Box::new(sdk))
+        }
+        #[cfg(chip_sdk)]
+        Backend::Sdk => Ok(Box::new(FfiSdk)),
+        #[cfg(feature = "sdk_stub")]
+        Backend::Stub => {
+            let sim = topology.sim()?;
+            for chip in (0..).map_while(|chip_id| sim.chip(chip_id)) {
+                unsafe { device_add_chip(&chip) }
+                    .to_result()
+                    .map_err(|err| CliError(format!("Failed to add stub chip: {}", err)))?;
+            }
+            Ok(Box::new(FfiSdk))
+        }
+        #[allow(unreachable_patterns)]
+        backend => fail!("Backend '{}' is not available in this build", backend),
+    }
+}
+
+#[derive(Debug)]
+struct Options {
+    backend: Backend,
+    topology: TopologySpec,
+    config: PathBuf,
+    hold: HoldTimers,
+    dampen: bool,
+    fail: Vec<SdkOp>,
+    dry_run: bool,
+    json: bool,
+    command: Vec<String>,
+}
+
+fn parse_options(args: impl IntoIterator<Item = String>) -> CliResult<Option<Options>> {
+    let mut options = Options {
+        backend: default_backend(),
+        topology: TopologySpec::default(),
+        config: env::var_os(LAC_CONFIG_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("lacctl.toml")),
+        hold: HoldTimers::default(),
+        dampen: false,
+        fail: Vec::new(),
+        dry_run: false,
+        json: false,
+        command: Vec::new(),
+    };
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| CliError(format!("Missing value for {}", name)))
+        };
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--backend" => options.backend = parse("backend", &value(&arg)?)?,
+            "--topology" => options.topology = parse("topology", &value(&arg)?)?,
+            "--config" => options.config = PathBuf::from(value(&arg)?),
+            "--hold-up" => {
+                options.hold.up = Duration::from_millis(parse("hold time", &value(&arg)?)?)
+            }
+            "--hold-down" => {
+                options.hold.down = Duration::from_millis(parse("hold time", &value(&arg)?)?)
+            }
+            "--dampen" => options.dampen = true,
+            "--fail" => options.fail.push(parse_op(&value(&arg)?)?),
+            "--dry-run" => options.dry_run = true,
+            "--json" => options.json = true,
+            _ => options.command.push(arg),
+        }
+    }
+    if options.command.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(options))
+}
+
+/// Rows printed either as an aligned table or as a JSON array of objects.
+struct Table {
+    columns: &'static [&'static str],
+    rows: Vec<Vec<Value>>,
+}
+
+impl Table {
+    fn new(columns: &'static [&'static str]) -> Self {
+        Table {
+            columns,
+            rows: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, row: Vec<Value>) {
+        self.rows.push(row);
+    }
+
+    fn cell(value: &Value) -> String {
+        match value {
+            Value::Null => "-".to_string(),
+            Value::String(s) => s.clone(),
+            Value::Array(values) if values.is_empty() => "-".to_string(),
+            Value::Array(values) => values.iter().map(Self::cell).collect::<Vec<_>>().join(","),
+            Value::Object(fields) => fields
+                .values()
+                .map(Self::cell)
+                .collect::<Vec<_>>()
+                .join(":"),
+            value => value.to_string(),
+        }
+    }
+
+    fn print(&self, json: bool) {
+        if json {
+            let rows: Vec<Value> = self
+                .rows
+                .iter()
+                .map(|row| {
+                    let fields = self.columns.iter().map(|c| c.replace(' ', "_"));
+                    Value::Object(fields.zip(row.iter().cloned()).collect())
+                })
+                .collect();
+            println!("{}", Value::Array(rows));
+            return;
+        }
+        let cells: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|row| row.iter().map(Self::cell).collect())
+            .collect();
+        let widths: Vec<usize> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                cells
+                    .iter()
+                    .map(|row| row[i].len())
+                    .fold(column.len(), usize::max)
+            })
+            .collect();
+        let line = |row: Vec<String>| {
+            let padded: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect();
+            println!("{}", padded.join("  ").trim_end());
+        };
+        line(self.columns.iter().map(|c| c.to_uppercase()).collect());
+        cells.into_iter().for_each(line);
+    }
+}
+
+fn string(value: impl ToString) -> Value {
+    Value::String(value.to_string())
+}
+
+fn optional(value: Option<impl ToString>) -> Value {
+    value.map_or(Value::Null, string)
+}
+
+fn link(status: LinkStatus) -> Value {
+    string(if status == LinkStatus::LINK_UP {
+        "up"
+    } else {
+        "down"
+    })
+}
+
+fn damping(status: Option<DampingStatus>) -> Value {
+    match status {
+        Some(status) if status.suppressed => json!(format!("suppressed ({})", status.penalty)),
+        Some(DampingStatus {
+            pending: Some(pending),
+            ..
+        }) => json!(format!("holding {}", Table::cell(&link(pending)))),
+        _ => Value::Null,
+    }
+}
+
+fn member_state(state: MemberState) -> &'static str {
+    match state {
+        MemberState::Active => "active",
+        MemberState::Standby => "standby",
+        MemberState::Down => "down",
+        MemberState::SpeedMismatch => "speed-mismatch",
+    }
+}
+
+fn group_state(state: GroupState) -> &'static str {
+    match state {
+        GroupState::Down => "down",
+        GroupState::Degraded => "degraded",
+        GroupState::Up => "up",
+    }
+}
+
+struct Session {
+    device: Device<Box<dyn ChipSdk>>,
+    lag: LagManager,
+    config: LacConfig,
+    /// MACs the saved configuration would program, standing in for the
+    /// device during a dry run.
+    staged_macs: Option<HashMap<PhyPortId, MacAddr>>,
+    options: Options,
+}
+
+impl Session {
+    fn open(options: Options) -> CliResult<Self> {
+        let device = Device::with_sdk(open_sdk(&options)?);
+        device.set_retry_policy(RetryPolicy::default());
+        device.set_hold_timers(options.hold);
+        device
+            .set_dampening(options.dampen.then(DampeningPolicy::default))
+            .map_err(|err| CliError(err.to_string()))?;
+        device
+            .activate()
+            .map_err(|err| CliError(format!("Failed to activate device: {}", err)))?;
+        let config = if options.config.exists() {
+            LacConfig::load(&options.config)?
+        } else {
+            LacConfig::default()
+        };
+        let mut lag = LagManager::new();
+        lag.sync_topology(&device.topology());
+        // A dry run reads the topology from the activated device, but only
+        // builds the groups in memory and programs no MAC.
+        let staged_macs = if options.dry_run {
+            config.validate(&device.topology())?;
+            let diff = config.diff(&lag, &device.programmed_macs());
+            diff.apply_groups(&mut lag)?;
+            Some(diff.macs().collect())
+        } else {
+            config.apply(&device, &mut lag, false)?;
+            None
+        };
+        Ok(Session {
+            device,
+            lag,
+            config,
+            staged_macs,
+            options,
+        })
+    }
+
+    fn programmed_macs(&self) -> HashMap<PhyPortId, MacAddr> {
+        match &self.staged_macs {
+            Some(macs) => macs.clone(),
+            None => self.device.programmed_macs(),
+        }
+    }
+
+    fn group_config(&mut self, id: GroupId) -> CliResult<&mut GroupConfig> {
+        match self.config.groups.iter_mut().find(|group| group.id == id) {
+            Some(group) => Ok(group),
+            None => fail!("Group {} does not exist", id),
+        }
+    }
+
+    /// Applies the edited configuration and saves it, printing the changes.
+    fn commit(&mut self) -> CliResult<()> {
+        let diff = if self.options.dry_run {
+            self.config.validate(&self.device.topology())?;
+            self.config.diff(&self.lag, &self.programmed_macs())
+        } else {
+            self.config.apply(&self.device, &mut self.lag, false)?
+        };
+        if !self.options.dry_run {
+            self.config.save(&self.options.config)?;
+        }
+        if self.options.json {
+            let changes: Vec<Value> = diff.changes.iter().map(string).collect();
+            println!(
+                "{}",
+                json!({ "dry_run": self.options.dry_run, "changes": changes })
+            );
+        } else if diff.is_empty() {
+            println!("No changes");
+        } else {
+            print!("{}", diff);
+        }
+        Ok(())
+    }
+
+    fn run(mut self) -> CliResult<()> {
+        let command = std::mem::take(&mut self.options.command);
+        let args: Vec<&str> = command.iter().map(String::as_str).collect();
+        match args.as_slice() {
+            ["chips"] => self.chips(),
+            ["ports"] => self.ports(),
+            ["groups"] => self.groups()?,
+            ["macs"] => self.macs(),
+            ["group", "create", id, flags @ ..] => self.create_group(parse("group", id)?, flags)?,
+            ["group", "delete", id] => {
+                let id = parse("group", id)?;
+                self.group_config(id)?;
+                self.config.groups.retain(|group| group.id != id);
+                self.commit()?;
+            }
+            ["group", "mac", id, mac] => {
+                let mac = parse_mac(mac)?;
+                self.group_config(parse("group", id)?)?.mac = mac;
+                self.commit()?;
+            }
+            ["member", "add", id, port] => {
+                let port = parse("port", port)?;
+                self.group_config(parse("group", id)?)?.members.push(port);
+                self.commit()?;
+            }
+            ["member", "remove", id, port] => {
+                let (id, port): (GroupId, PhyPortId) = (parse("group", id)?, parse("port", port)?);
+                let group = self.group_config(id)?;
+                if !group.members.contains(&port) {
+                    fail!("Port {} is not a member of group {}", port, id);
+                }
+                group.members.retain(|member| *member != port);
+                self.commit()?;
+            }
+            ["mac", "set", port, mac] => self.set_mac(parse("port", port)?, parse_mac(mac)?)?,
+            ["events", flags @ ..] => self.events(flags)?,
+            _ => fail!("Unknown command '{}', see --help", command.join(" ")),
+        }
+        Ok(())
+    }
+
+    fn chips(&self) {
+        let mut table = Table::new(&["chip", "ports", "up"]);
+        for chip in self.device.chips() {
+            table.push(vec![
+                json!(chip.id()),
+                json!(chip.ports().count()),
+                json!(chip.ports().filter(|port| port.is_up()).count()),
+            ]);
+        }
+        table.print(self.options.json);
+    }
+
+    fn ports(&self) {
+        let mut table = Table::new(&["port", "speed", "link", "damping", "group", "mac"]);
+        let macs = self.programmed_macs();
+        for port in self.device.ports() {
+            table.push(vec![
+                string(port.id),
+                string(port.speed),
+                link(port.status),
+                damping(self.device.damping_status(&port.id)),
+                json!(self.lag.group_of(&port.id)),
+                optional(macs.get(&port.id)),
+            ]);
+        }
+        table.print(self.options.json);
+    }
+
+    fn groups(&self) -> CliResult<()> {
+        let mut table = Table::new(&[
+            "group",
+            "state",
+            "speed",
+            "hash",
+            "min links",
+            "mac",
+            "members",
+        ]);
+        for group in self.lag.groups() {
+            let status = self.lag.group_status(group.id())?;
+            let members: Vec<Value> = status
+                .members
+                .iter()
+                .map(|(port, state)| json!({ "port": string(port), "state": member_state(*state) }))
+                .collect();
+            table.push(vec![
+                json!(group.id()),
+                string(group_state(status.state)),
+                optional(status.speed),
+                string(group.hash_policy()),
+                json!(group.min_links()),
+                optional(group.mac()),
+                Value::Array(members),
+            ]);
+        }
+        table.print(self.options.json);
+        Ok(())
+    }
+
+    fn macs(&self) {
+        let mut table = Table::new(&["port", "mac", "group"]);
+        let mut macs: Vec<_> = self.programmed_macs().into_iter().collect();
+        macs.sort_by_key(|(port, _)| *port);
+        for (port, mac) in macs {
+            table.push(vec![
+                string(port),
+                string(mac),
+                json!(self.lag.group_of(&port)),
+            ]);
+        }
+        table.print(self.options.json);
+    }
+
+    fn create_group(&mut self, id: GroupId, mut flags: &[&str]) -> CliResult<()> {
+        if self.config.groups.iter().any(|group| group.id == id) {
+            fail!("Group {} already exists", id);
+        }
+        let mut group = GroupConfig::new(id);
+        let mut mac_pool = None;
+        while let [flag, value, rest @ ..] = flags {
+            match *flag {
+                "--hash" => {
+                    group.hash_policy = serde_json::from_value(string(value))
+                        .map_err(|_| CliError(format!("Invalid hash policy '{}'", value)))?
+                }
+                "--min-links" => group.min_links = parse("min links", value)?,
+                "--speed" => group.speed = Some(parse("speed", value)?),
+                "--mac-pool" => mac_pool = Some(PathBuf::from(value)),
+                _ => fail!("Unknown option '{}'", flag),
+            }
+            flags = rest;
+        }
+        if let [flag] = flags {
+            fail!("Missing value for {}", flag);
+        }
+        let pool = match &mac_pool {
+            Some(path) => {
+                let mut pool = MacPool::load(path)?;
+                group.mac = Some(pool.allocate(MacOwner::Group(id), &self.programmed_macs())?);
+                Some(pool)
+            }
+            None => None,
+        };
+        self.config.groups.push(group);
+        self.commit()?;
+        if let (Some(pool), Some(path), false) = (pool, mac_pool, self.options.dry_run) {
+            pool.save(path)?;
+        }
+        Ok(())
+    }
+
+    fn set_mac(&mut self, port: PhyPortId, mac: Option<MacAddr>) -> CliResult<()> {
+        match self
+            .config
+            .ports
+            .iter_mut()
+            .find(|config| config.port == port)
+        {
+            Some(config) => config.mac = mac,
+            None => self.config.ports.push(PortConfig {
+                port,
+                priority: None,
+                mac,
+            }),
+        }
+        self.commit()
+    }
+
+    fn events(&mut self, mut flags: &[&str]) -> CliResult<()> {
+        let mut count = None;
+        let mut timeout = None;
+        while let [flag, value, rest @ ..] = flags {
+            match *flag {
+                "--count" => count = Some(parse::<usize>("count", value)?),
+                "--timeout" => {
+                    timeout = Some(
+                        Duration::try_from_secs_f64(parse("timeout", value)?).map_err(|err| {
+                            CliError(format!("Invalid timeout '{}': {}", value, err))
+                        })?,
+                    );
+                }
+                _ => fail!("Unknown option '{}'", flag),
+            }
+            flags = rest;
+        }
+        if let [flag] = flags {
+            fail!("Missing value for {}", flag);
+        }
+        let events = self
+            .device
+            .link_events(64)
+            .map_err(|err| CliError(format!("Failed to subscribe to link events: {}", err)))?;
+        let start = Instant::now();
+        let deadline = timeout.map(|timeout| start + timeout);
+        let mut received = 0;
+        while count.is_none_or(|count| received < count) {
+            let event = loop {
+                let timers = self.device.next_link_deadline();
+                let wake = match (deadline, timers) {
+                    (Some(deadline), Some(timers)) => Some(deadline.min(timers)),
+                    (deadline, timers) => deadline.or(timers),
+                };
+                let Some(wake) = wake else {
+                    break events.recv();
+                };
+                let event = events.recv_timeout(wake.saturating_duration_since(Instant::now()));
+                if event.is_some() || deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                    break event;
+                }
+                self.device.run_link_timers();
+            };
+            let Some(event) = event else {
+                break;
+            };
+            received += 1;
+            self.lag.update_link_status(event.port, event.status);
+            let elapsed = event.timestamp.duration_since(start).as_secs_f64();
+            let group = self.lag.group_of(&event.port);
+            if self.options.json {
+                println!(
+                    "{}",
+                    json!({
+                        "elapsed": elapsed,
+                        "port": string(event.port),
+                        "link": link(event.status),
+                        "group": group,
+                        "missed": event.missed,
+                    })
+                );
+            } else {
+                println!(
+                    "{:>10.3}s  {}  {}  group {}  missed {}",
+                    elapsed,
+                    event.port,
+                    Table::cell(&link(event.status)),
+                    Table::cell(&optional(group)),
+                    event.missed
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+fn main() -> ExitCode {
+    let options = match parse_options(env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            print!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("lacctl: {}", err);
+            return ExitCode::from(2);
+        }
+    };
+    match Session::open(options).and_then(Session::run) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("lacctl: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -5,6 +5,7 @@ pub use bindings::*;
 
 use std::error::Error;
 use std::fmt;
+use std::str::FromStr;
 
 pub type SdkResult = Result<(), ChipSdkError>;
 pub const SDK_OK: SdkResult = Ok(());
@@ -37,11 +38,52 @@ impl ChipSdkError {
             Err(self)
         }
     }
+
+    /// Whether the call may succeed if repeated later.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            ChipSdkError::CHIP_SDK_BUSY | ChipSdkError::CHIP_SDK_TIMEOUT
+        )
+    }
 }
 
 pub type ChipId = i32;
 type LocalPortId = i32;
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct PhyPortId(pub ChipId, pub LocalPortId);
+
+impl fmt::Display for PhyPortId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.0, self.1)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhyPortIdParseError(String);
+
+impl fmt::Display for PhyPortIdParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid port '{}', expected chip/port", self.0)
+    }
+}
+
+impl Error for PhyPortIdParseError {}
+
+impl FromStr for PhyPortId {
+    type Err = PhyPortIdParseError;
+
+    /// Parses the `chip/port` form produced by `Display`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || PhyPortIdParseError(s.to_string());
+        let (chip, port) = s.split_once('/').ok_or_else(error)?;
+        Ok(PhyPortId(
+            chip.parse().map_err(|_| error())?,
+            port.parse().map_err(|_| error())?,
+        ))
+    }
+}
+
 pub type SwitchChip = SwitchChipTag;
 pub type PhyPort = PhyPortTag;
 pub type Mac = MacTag;
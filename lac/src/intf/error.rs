@@ -0,0 +1,144 @@
+use crate::ffi::*;
+use crate::lac::{
+    ConfigError, DeviceError, DeviceState, GroupId, LagError, MacAddr, TopologyError,
+};
+use crate::sdk::SdkOp;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LacError {
+    Sdk {
+        op: SdkOp,
+        port: Option<PhyPortId>,
+        source: ChipSdkError,
+    },
+    InvalidConfig(String),
+    PortAlreadyAggregated {
+        port: PhyPortId,
+        group: GroupId,
+    },
+    GroupNotFound(GroupId),
+    PortNotMember {
+        port: PhyPortId,
+        group: GroupId,
+    },
+    InvalidMinLinks {
+        group: GroupId,
+        min_links: usize,
+    },
+    /// The SDK reported chips or ports that cannot be used.
+    Topology(TopologyError),
+    InvalidMac(MacAddr),
+    Uninitialized,
+    /// The device cannot run the operation in its current state.
+    InvalidState(DeviceState),
+}
+
+impl LacError {
+    /// Wraps an SDK error of `op`, for use with `map_err`.
+    pub fn sdk(op: SdkOp, port: Option<PhyPortId>) -> impl FnOnce(ChipSdkError) -> LacError {
+        move |source| LacError::Sdk { op, port, source }
+    }
+
+    /// Wraps a device error of `op`, for use with `map_err`.
+    pub fn device(op: SdkOp, port: Option<PhyPortId>) -> impl FnOnce(DeviceError) -> LacError {
+        move |err| match err {
+            DeviceError::InvalidState { state, .. } => LacError::InvalidState(state),
+            DeviceError::Topology(err) => LacError::Topology(err),
+            DeviceError::InvalidMac(mac) => LacError::InvalidMac(mac),
+            DeviceError::Sdk(source) => LacError::Sdk { op, port, source },
+        }
+    }
+
+    /// Stable code shown to operators. SDK failures use 100 plus the
+    /// `ChipSdkError` value.
+    pub fn code(&self) -> u32 {
+        match self {
+            LacError::Uninitialized => 1,
+            LacError::InvalidConfig(_) => 2,
+            LacError::PortAlreadyAggregated { .. } => 4,
+            LacError::InvalidState(_) => 5,
+            LacError::GroupNotFound(_) => 6,
+            LacError::PortNotMember { .. } => 7,
+            LacError::InvalidMinLinks { .. } => 8,
+            LacError::Topology(_) => 9,
+            LacError::InvalidMac(_) => 10,
+            LacError::Sdk { source, .. } => 100 + *source as u32,
+        }
+    }
+}
+
+impl fmt::Display for LacError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LacError::Sdk {
+                op,
+                port: Some(port),
+                source,
+            } => write!(f, "SDK {} failed on port {}: {}", op, port, source),
+            LacError::Sdk {
+                op,
+                port: None,
+                source,
+            } => write!(f, "SDK {} failed: {}", op, source),
+            LacError::InvalidConfig(reason) => write!(f, "Invalid configuration: {}", reason),
+            LacError::PortAlreadyAggregated { port, group } => {
+                write!(f, "Port {} already belongs to group {}", port, group)
+            }
+            LacError::GroupNotFound(group) => write!(f, "Group {} not found", group),
+            LacError::PortNotMember { port, group } => {
+                write!(f, "Port {} is not a member of group {}", port, group)
+            }
+            LacError::InvalidMinLinks { group, min_links } => write!(
+                f,
+                "Group {} cannot require {} links, allowed are 1 to {}",
+                group, min_links, CHIP_SDK_PHY_PORT_PER_GROUP_MAX
+            ),
+            LacError::Topology(err) => write!(f, "Invalid topology: {}", err),
+            LacError::InvalidMac(mac) => write!(f, "{} is not a unicast MAC", mac),
+            LacError::Uninitialized => write!(f, "LAC is not initialized"),
+            LacError::InvalidState(state) => write!(f, "Device is {}", state),
+        }
+    }
+}
+
+impl Error for LacError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LacError::Sdk { source, .. } => Some(source),
+            LacError::Topology(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<LagError> for LacError {
+    fn from(err: LagError) -> Self {
+        match err {
+            LagError::PortAlreadyAggregated(port, group) => {
+                LacError::PortAlreadyAggregated { port, group }
+            }
+            LagError::GroupNotFound(group) => LacError::GroupNotFound(group),
+            LagError::PortNotMember(port, group) => LacError::PortNotMember { port, group },
+            LagError::InvalidMinLinks(group, min_links) => {
+                LacError::InvalidMinLinks { group, min_links }
+            }
+            err @ LagError::GroupExists(_) => LacError::InvalidConfig(err.to_string()),
+        }
+    }
+}
+
+impl From<ConfigError> for LacError {
+    fn from(err: ConfigError) -> Self {
+        match err {
+            ConfigError::Lag(err) => err.into(),
+            ConfigError::Transaction(err) => {
+                LacError::device(SdkOp::SetMac, Some(err.op.port()))(err.error)
+            }
+            err => LacError::InvalidConfig(err.to_string()),
+        }
+    }
+}
+
+pub type LacResult<T> = Result<T, LacError>;
@@ -1,14 +1,324 @@
-pub enum LacError {
-    SdkError,
-    Uninitialized,
+mod error;
+
+pub use crate::lac::PortSpeed;
+pub use error::{LacError, LacResult};
+
+use crate::ffi::*;
+use crate::lac::{
+    ConfigDiff, DampeningPolicy, Device, HoldTimers, LacConfig, LagEvent, LagEventHandler,
+    LagManager, LagObserver, LinkSubscription, LinkTelemetry, MembershipChange, TelemetrySnapshot,
+    TopologyChange,
+};
+use crate::sdk::{ChipSdk, FfiSdk, SdkOp};
+use std::env;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Environment variable naming the configuration file `lac_init` applies.
+pub const LAC_CONFIG_ENV: &str = "LAC_CONFIG";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PortInfo {
+    pub id: PhyPortId,
+    pub speed: PortSpeed,
+    pub status: LinkStatus,
+    /// Held down by flap dampening.
+    pub suppressed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChipInfo {
+    pub chip_id: ChipId,
+    pub ports: Vec<PortInfo>,
 }
 
-pub type LacResult<T> = Result<T, LacError>;
+struct LacContext {
+    /// Tells the link changes of this context from those of one it
+    /// replaced.
+    generation: u64,
+    device: Device<Box<dyn ChipSdk>>,
+    lag: LagManager,
+    telemetry: LinkTelemetry,
+    observer: LagObserver,
+    event_handler: Option<Arc<LagEventHandler>>,
+    link_subscription: LinkSubscription,
+}
 
+/// Events waiting to be delivered once the context lock is released, so
+/// that handlers may call back into lac.
+struct Notification {
+    handler: Option<Arc<LagEventHandler>>,
+    events: Vec<LagEvent>,
+}
+
+impl Notification {
+    fn deliver(self) {
+        if let Some(handler) = self.handler {
+            self.events.iter().for_each(|event| handler(event));
+        }
+    }
+}
+
+static CONTEXT: Mutex<Option<LacContext>> = Mutex::new(None);
+
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+impl LacContext {
+    /// Updates the group counters and events after a change.
+    fn observe(&mut self, trigger: Option<PhyPortId>) -> Notification {
+        self.telemetry.observe_groups(&self.lag);
+        let macs = self.device.programmed_macs();
+        Notification {
+            handler: self.event_handler.clone(),
+            events: self.observer.observe(&self.lag, &macs, trigger),
+        }
+    }
+
+    /// Catches up with the link changes dropped while the context was not
+    /// yet installed.
+    fn resync(&mut self) -> Notification {
+        let topology = self.device.topology();
+        self.lag.sync_topology(&topology);
+        for port in topology.ports() {
+            self.telemetry.record_link(port.id, port.status);
+        }
+        self.observe(None)
+    }
+
+    /// Activates a device on `sdk` and applies `config` to it. Link changes
+    /// only reach the context once it is installed in `CONTEXT`, which then
+    /// has to `resync`.
+    fn new(sdk: Box<dyn ChipSdk>, config: Option<&LacConfig>) -> LacResult<Self> {
+        let device = Device::with_sdk(sdk);
+        device.activate().map_err(|err| {
+            let op = device
+                .last_attempts()
+                .last()
+                .map_or(SdkOp::Init, |attempt| attempt.op);
+            LacError::device(op, None)(err)
+        })?;
+        let mut lag = LagManager::new();
+        lag.sync_topology(&device.topology());
+        if let Some(config) = config {
+            if let Err(err) = config.apply(&device, &mut lag, false) {
+                let _ = device.deactivate();
+                return Err(err.into());
+            }
+        }
+        let mut telemetry = LinkTelemetry::new();
+        telemetry.sync_topology(&device.topology());
+        telemetry.observe_groups(&lag);
+        let mut observer = LagObserver::new();
+        observer.observe(&lag, &device.programmed_macs(), None);
+        let generation = NEXT_GENERATION.fetch_add(1, Ordering::Relaxed);
+        let link_subscription = device
+            .subscribe_link_status(Box::new(move |port, status| {
+                let notification = CONTEXT
+                    .lock()
+                    .unwrap()
+                    .as_mut()
+                    .filter(|context| context.generation == generation)
+                    .map(|context| {
+                        context.lag.update_link_status(port, status);
+                        context.telemetry.record_link(port, status);
+                        context.observe(Some(port))
+                    });
+                if let Some(notification) = notification {
+                    notification.deliver();
+                }
+            }))
+            .map_err(LacError::device(SdkOp::SetLinkStatusHandler, None))?;
+        Ok(LacContext {
+            generation,
+            device,
+            lag,
+            telemetry,
+            observer,
+            event_handler: None,
+            link_subscription,
+        })
+    }
+
+    fn shut_down(self) -> LacResult<()> {
+        self.link_subscription.unsubscribe();
+        self.device
+            .deactivate()
+            .map_err(LacError::device(SdkOp::SetLinkStatusHandler, None))
+    }
+}
+
+/// Initializes the chip SDK and applies the configuration file named by
+/// `LAC_CONFIG`, if set.
 pub fn lac_init() -> LacResult<()> {
+    let config = env::var_os(LAC_CONFIG_ENV)
+        .map(LacConfig::load)
+        .transpose()?;
+    lac_init_with_config(FfiSdk, config.as_ref())
+}
+
+/// Like `lac_init`, but drives `sdk` instead of the linked chip SDK and
+/// applies no configuration.
+pub fn lac_init_with_sdk(sdk: impl ChipSdk + 'static) -> LacResult<()> {
+    lac_init_with_config(sdk, None)
+}
+
+/// Like `lac_init_with_sdk`, applying `config` if given. The new device is
+/// set up before replacing a running one, which is left alone if that
+/// fails. An error shutting down the replaced device is returned with the
+/// new one in place.
+pub fn lac_init_with_config(
+    sdk: impl ChipSdk + 'static,
+    config: Option<&LacConfig>,
+) -> LacResult<()> {
+    let context = LacContext::new(Box::new(sdk), config)?;
+    let device = context.device.clone();
+    let generation = context.generation;
+    let previous = CONTEXT.lock().unwrap().replace(context);
+    let result = match previous {
+        Some(previous) => {
+            let result = previous.shut_down();
+            // The previous device may share the SDK, whose link handler it
+            // just cleared.
+            device
+                .register_link_sink()
+                .map_err(LacError::device(SdkOp::SetLinkStatusHandler, None))?;
+            result
+        }
+        None => Ok(()),
+    };
+    let notification = CONTEXT
+        .lock()
+        .unwrap()
+        .as_mut()
+        .filter(|context| context.generation == generation)
+        .map(LacContext::resync);
+    if let Some(notification) = notification {
+        notification.deliver();
+    }
+    result
+}
+
+/// Shuts the device down and drops all groups. `lac_init` may be called
+/// again afterwards.
+pub fn lac_deinit() -> LacResult<()> {
+    let context = CONTEXT.lock().unwrap().take();
+    context.ok_or(LacError::Uninitialized)?.shut_down()
+}
+
+/// Applies `config` to the running device, or with `dry_run` only reports
+/// what would change.
+pub fn lac_apply_config(config: &LacConfig, dry_run: bool) -> LacResult<ConfigDiff> {
+    let mut guard = CONTEXT.lock().unwrap();
+    let context = guard.as_mut().ok_or(LacError::Uninitialized)?;
+    let result = config.apply(&context.device, &mut context.lag, dry_run);
+    let notification = context.observe(None);
+    drop(guard);
+    notification.deliver();
+    Ok(result?)
+}
+
+/// Reads the chips from the SDK again and follows the changes: members on
+/// removed ports leave their groups, and rejoin them with the group MAC when
+/// the port comes back.
+pub fn lac_rescan() -> LacResult<Vec<TopologyChange>> {
+    let mut guard = CONTEXT.lock().unwrap();
+    let context = guard.as_mut().ok_or(LacError::Uninitialized)?;
+    let changes = context
+        .device
+        .rescan()
+        .map_err(LacError::device(SdkOp::Init, None))?;
+    let topology = context.device.topology();
+    let membership = context.lag.apply_topology_changes(&changes);
+    context.lag.sync_topology(&topology);
+    context.telemetry.sync_topology(&topology);
+    let result = membership.into_iter().try_for_each(|change| {
+        let MembershipChange::Restored(id, port) = change else {
+            return Ok(());
+        };
+        match context.lag.group(id).and_then(|group| group.mac()) {
+            Some(mac) => context
+                .device
+                .set_mac(&port, mac)
+                .map_err(LacError::device(SdkOp::SetMac, Some(port))),
+            None => Ok(()),
+        }
+    });
+    let notification = context.observe(None);
+    drop(guard);
+    notification.deliver();
+    result.map(|()| changes)
+}
+
+/// Installs the handler receiving group and member events, or removes it
+/// with `None`. Handlers run without the lac lock held and may call back
+/// into lac.
+pub fn lac_set_event_handler(handler: Option<LagEventHandler>) -> LacResult<()> {
+    let mut guard = CONTEXT.lock().unwrap();
+    let context = guard.as_mut().ok_or(LacError::Uninitialized)?;
+    context.event_handler = handler.map(Arc::new);
     Ok(())
 }
 
-pub fn lac_query_chip_info() -> LacResult<()> {
+/// Sets the hold timers of all ports and the flap dampening policy. Only
+/// stable link changes then reach the groups; `lac_run_link_timers` must be
+/// called to deliver those waiting for a timer.
+pub fn lac_set_link_dampening(hold: HoldTimers, policy: Option<DampeningPolicy>) -> LacResult<()> {
+    let guard = CONTEXT.lock().unwrap();
+    let device = &guard.as_ref().ok_or(LacError::Uninitialized)?.device;
+    device
+        .set_dampening(policy)
+        .map_err(|err| LacError::InvalidConfig(err.to_string()))?;
+    device.set_hold_timers(hold);
     Ok(())
 }
+
+/// Delivers link changes whose hold timer ran out, returning how many.
+pub fn lac_run_link_timers() -> LacResult<usize> {
+    // Delivery calls back into the context, so the lock cannot be held.
+    let timers = {
+        let guard = CONTEXT.lock().unwrap();
+        guard
+            .as_ref()
+            .ok_or(LacError::Uninitialized)?
+            .device
+            .link_timers()
+    };
+    Ok(timers.run())
+}
+
+/// Link counters of every port and group since `lac_init`.
+pub fn lac_telemetry() -> LacResult<TelemetrySnapshot> {
+    let guard = CONTEXT.lock().unwrap();
+    let context = guard.as_ref().ok_or(LacError::Uninitialized)?;
+    Ok(context.telemetry.snapshot())
+}
+
+/// Configuration reproducing the current groups and MACs.
+pub fn lac_current_config() -> LacResult<LacConfig> {
+    let guard = CONTEXT.lock().unwrap();
+    let context = guard.as_ref().ok_or(LacError::Uninitialized)?;
+    Ok(LacConfig::capture(&context.lag, &context.device))
+}
+
+pub fn lac_query_chip_info() -> LacResult<Vec<ChipInfo>> {
+    let guard = CONTEXT.lock().unwrap();
+    let device = &guard.as_ref().ok_or(LacError::Uninitialized)?.device;
+    let chips = device
+        .chips()
+        .map(|chip| ChipInfo {
+            chip_id: chip.id(),
+            ports: chip
+                .ports()
+                .map(|port| PortInfo {
+                    id: port.id,
+                    speed: port.speed,
+                    status: port.status,
+                    suppressed: device
+                        .damping_status(&port.id)
+                        .is_some_and(|damping| damping.suppressed),
+                })
+                .collect(),
+        })
+        .collect();
+    Ok(chips)
+}
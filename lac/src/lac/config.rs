@@ -0,0 +1,537 @@
+use super::device::Device;
+use super::hash::HashPolicy;
+use super::lag::{GroupId, LagError, LagManager, DEFAULT_PORT_PRIORITY};
+use super::mac::MacAddr;
+use super::topology::{PortSpeed, Topology};
+use super::transaction::{LagOp, TransactionError};
+use crate::ffi::*;
+use crate::sdk::ChipSdk;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Stores a value as the string of its `Display` form.
+macro_rules! serde_via_str {
+    ($($ty:ty),*) => {$(
+        impl Serialize for $ty {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let text = String::deserialize(deserializer)?;
+                <$ty>::from_str(&text).map_err(serde::de::Error::custom)
+            }
+        }
+    )*};
+}
+
+serde_via_str!(PhyPortId, MacAddr, PortSpeed);
+
+fn default_min_links() -> usize {
+    1
+}
+
+fn is_default_min_links(min_links: &usize) -> bool {
+    *min_links == 1
+}
+
+fn is_default_hash_policy(policy: &HashPolicy) -> bool {
+    *policy == HashPolicy::default()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GroupConfig {
+    pub id: GroupId,
+    #[serde(default)]
+    pub members: Vec<PhyPortId>,
+    #[serde(default, skip_serializing_if = "is_default_hash_policy")]
+    pub hash_policy: HashPolicy,
+    #[serde(
+        default = "default_min_links",
+        skip_serializing_if = "is_default_min_links"
+    )]
+    pub min_links: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speed: Option<PortSpeed>,
+    /// Programmed on every member.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mac: Option<MacAddr>,
+}
+
+impl GroupConfig {
+    pub fn new(id: GroupId) -> Self {
+        GroupConfig {
+            id,
+            members: Vec::new(),
+            hash_policy: HashPolicy::default(),
+            min_links: default_min_links(),
+            speed: None,
+            mac: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PortConfig {
+    pub port: PhyPortId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u16>,
+    /// Only allowed on ports whose group has no MAC.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mac: Option<MacAddr>,
+}
+
+/// Groups, members, priorities, MACs and policies to restore on start-up,
+/// stored as TOML.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LacConfig {
+    #[serde(default, rename = "group", skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<GroupConfig>,
+    #[serde(default, rename = "port", skip_serializing_if = "Vec::is_empty")]
+    pub ports: Vec<PortConfig>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Parse(String),
+    Serialize(String),
+    UnknownPort(PhyPortId),
+    DuplicateGroup(GroupId),
+    DuplicatePort(PhyPortId),
+    DuplicateMac(MacAddr),
+    MulticastMac(MacAddr),
+    /// A port MAC was set on a member of a group with its own MAC.
+    MacConflict(PhyPortId, GroupId),
+    SpeedMismatch {
+        port: PhyPortId,
+        speed: PortSpeed,
+        expected: PortSpeed,
+    },
+    Lag(LagError),
+    /// Programming MACs failed; the earlier ones were rolled back.
+    Transaction(TransactionError),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "{}", err),
+            ConfigError::Parse(err) => write!(f, "Invalid configuration file: {}", err),
+            ConfigError::Serialize(err) => write!(f, "Failed to serialize configuration: {}", err),
+            ConfigError::UnknownPort(port) => write!(f, "Port {} does not exist", port),
+            ConfigError::DuplicateGroup(id) => write!(f, "Group {} is configured twice", id),
+            ConfigError::DuplicatePort(port) => write!(f, "Port {} is configured twice", port),
+            ConfigError::DuplicateMac(mac) => write!(f, "MAC {} is configured twice", mac),
+            ConfigError::MulticastMac(mac) => write!(f, "MAC {} is not unicast", mac),
+            ConfigError::MacConflict(port, id) => write!(
+                f,
+                "Port {} has its own MAC but group {} sets one for all members",
+                port, id
+            ),
+            ConfigError::SpeedMismatch {
+                port,
+                speed,
+                expected,
+            } => write!(
+                f,
+                "Port {} runs at {}, its group requires {}",
+                port, speed, expected
+            ),
+            ConfigError::Lag(err) => write!(f, "{}", err),
+            ConfigError::Transaction(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConfigError::Io(err) => Some(err),
+            ConfigError::Lag(err) => Some(err),
+            ConfigError::Transaction(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ConfigError {
+    fn from(err: io::Error) -> Self {
+        ConfigError::Io(err)
+    }
+}
+
+impl From<LagError> for ConfigError {
+    fn from(err: LagError) -> Self {
+        ConfigError::Lag(err)
+    }
+}
+
+pub type ConfigResult<T> = Result<T, ConfigError>;
+
+/// One step needed to bring the device in line with a configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigChange {
+    DeleteGroup(GroupId),
+    RemoveMember(GroupId, PhyPortId),
+    CreateGroup(GroupId),
+    AddMember(GroupId, PhyPortId),
+    SetHashPolicy(GroupId, HashPolicy),
+    SetMinLinks(GroupId, usize),
+    SetGroupSpeed(GroupId, Option<PortSpeed>),
+    SetGroupMac(GroupId, Option<MacAddr>),
+    SetPortPriority(PhyPortId, u16),
+    SetMac(PhyPortId, MacAddr),
+}
+
+impl fmt::Display for ConfigChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigChange::DeleteGroup(id) => write!(f, "delete group {}", id),
+            ConfigChange::RemoveMember(id, port) => {
+                write!(f, "remove port {} from group {}", port, id)
+            }
+            ConfigChange::CreateGroup(id) => write!(f, "create group {}", id),
+            ConfigChange::AddMember(id, port) => write!(f, "add port {} to group {}", port, id),
+            ConfigChange::SetHashPolicy(id, policy) => {
+                write!(f, "set group {} hash policy {}", id, policy)
+            }
+            ConfigChange::SetMinLinks(id, min_links) => {
+                write!(f, "set group {} min links {}", id, min_links)
+            }
+            ConfigChange::SetGroupSpeed(id, Some(speed)) => {
+                write!(f, "set group {} speed {}", id, speed)
+            }
+            ConfigChange::SetGroupSpeed(id, None) => write!(f, "clear group {} speed", id),
+            ConfigChange::SetGroupMac(id, Some(mac)) => write!(f, "set group {} mac {}", id, mac),
+            ConfigChange::SetGroupMac(id, None) => write!(f, "clear group {} mac", id),
+            ConfigChange::SetPortPriority(port, priority) => {
+                write!(f, "set port {} priority {}", port, priority)
+            }
+            ConfigChange::SetMac(port, mac) => write!(f, "set port {} mac {}", port, mac),
+        }
+    }
+}
+
+/// Changes in the order they are applied.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ConfigDiff {
+    pub changes: Vec<ConfigChange>,
+}
+
+impl ConfigDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Carries out the group changes only, leaving the device alone. On
+    /// failure `lag` is unchanged.
+    pub fn apply_groups(&self, lag: &mut LagManager) -> ConfigResult<()> {
+        let mut transaction = lag.transaction();
+        for change in &self.changes {
+            let op = match *change {
+                ConfigChange::DeleteGroup(id) => LagOp::DeleteGroup(id),
+                ConfigChange::RemoveMember(id, port) => LagOp::RemoveMember(id, port),
+                ConfigChange::CreateGroup(id) => LagOp::CreateGroup(id),
+                ConfigChange::AddMember(id, port) => LagOp::AddMember(id, port),
+                ConfigChange::SetHashPolicy(id, policy) => LagOp::SetHashPolicy(id, policy),
+                ConfigChange::SetMinLinks(id, min_links) => LagOp::SetMinLinks(id, min_links),
+                ConfigChange::SetGroupSpeed(id, speed) => LagOp::SetGroupSpeed(id, speed),
+                ConfigChange::SetGroupMac(id, mac) => LagOp::SetGroupMac(id, mac),
+                ConfigChange::SetPortPriority(port, priority) => {
+                    LagOp::SetPortPriority(port, priority)
+                }
+                ConfigChange::SetMac(..) => continue,
+            };
+            transaction.stage(op);
+        }
+        transaction
+            .commit()
+            .map_err(|err| ConfigError::Lag(err.error))
+    }
+
+    /// MACs the changes program, by port.
+    pub fn macs(&self) -> impl Iterator<Item = (PhyPortId, MacAddr)> + '_ {
+        self.changes.iter().filter_map(|change| match *change {
+            ConfigChange::SetMac(port, mac) => Some((port, mac)),
+            _ => None,
+        })
+    }
+
+    /// Carries out every change, or none of them. Group changes are made on
+    /// a copy of `lag`, which replaces it once the MACs are programmed in a
+    /// device transaction.
+    pub fn apply<S: ChipSdk>(&self, device: &Device<S>, lag: &mut LagManager) -> ConfigResult<()> {
+        let mut staged = lag.clone();
+        self.apply_groups(&mut staged)?;
+        let mut transaction = device.transaction();
+        for (port, mac) in self.macs() {
+            transaction.set_mac(port, mac);
+        }
+        transaction.commit().map_err(ConfigError::Transaction)?;
+        *lag = staged;
+        Ok(())
+    }
+}
+
+impl fmt::Display for ConfigDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
+
+impl LacConfig {
+    pub fn from_toml(text: &str) -> ConfigResult<Self> {
+        toml::from_str(text).map_err(|err| ConfigError::Parse(err.to_string()))
+    }
+
+    pub fn to_toml(&self) -> ConfigResult<String> {
+        toml::to_string(self).map_err(|err| ConfigError::Serialize(err.to_string()))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> ConfigResult<Self> {
+        Self::from_toml(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> ConfigResult<()> {
+        fs::write(path, self.to_toml()?)?;
+        Ok(())
+    }
+
+    /// Captures the groups of `lag` and the MACs programmed on `device`.
+    pub fn capture<S: ChipSdk>(lag: &LagManager, device: &Device<S>) -> Self {
+        let groups = lag
+            .groups()
+            .map(|group| GroupConfig {
+                id: group.id(),
+                members: group.members().to_vec(),
+                hash_policy: group.hash_policy(),
+                min_links: group.min_links(),
+                speed: group.speed(),
+                mac: group.mac(),
+            })
+            .collect();
+
+        let mut ports: BTreeMap<PhyPortId, PortConfig> = BTreeMap::new();
+        fn port(ports: &mut BTreeMap<PhyPortId, PortConfig>, id: PhyPortId) -> &mut PortConfig {
+            ports.entry(id).or_insert(PortConfig {
+                port: id,
+                priority: None,
+                mac: None,
+            })
+        }
+        for (id, priority) in lag.port_priorities() {
+            if *priority != DEFAULT_PORT_PRIORITY {
+                port(&mut ports, *id).priority = Some(*priority);
+            }
+        }
+        for (id, mac) in &device.programmed_macs() {
+            let group_mac = lag
+                .group_of(id)
+                .and_then(|group| lag.group(group))
+                .and_then(|group| group.mac());
+            if group_mac.is_none() {
+                port(&mut ports, *id).mac = Some(*mac);
+            }
+        }
+        LacConfig {
+            groups,
+            ports: ports.into_values().collect(),
+        }
+    }
+
+    /// MAC each port should carry.
+    fn port_macs(&self) -> BTreeMap<PhyPortId, MacAddr> {
+        let mut macs = BTreeMap::new();
+        for group in &self.groups {
+            if let Some(mac) = group.mac {
+                macs.extend(group.members.iter().map(|port| (*port, mac)));
+            }
+        }
+        macs.extend(
+            self.ports
+                .iter()
+                .filter_map(|port| Some((port.port, port.mac?))),
+        );
+        macs
+    }
+
+    /// Checks the configuration is consistent and only refers to ports of
+    /// `topology`.
+    pub fn validate(&self, topology: &Topology) -> ConfigResult<()> {
+        let check_port =
+            |port: &PhyPortId| topology.port(port).ok_or(ConfigError::UnknownPort(*port));
+        let check_mac = |mac: &MacAddr| {
+            if mac.is_multicast() {
+                return Err(ConfigError::MulticastMac(*mac));
+            }
+            Ok(())
+        };
+
+        let mut group_ids = BTreeSet::new();
+        let mut owners = HashMap::new();
+        let mut macs = BTreeSet::new();
+        for group in &self.groups {
+            if !group_ids.insert(group.id) {
+                return Err(ConfigError::DuplicateGroup(group.id));
+            }
+            if !(1..=CHIP_SDK_PHY_PORT_PER_GROUP_MAX).contains(&group.min_links) {
+                return Err(LagError::InvalidMinLinks(group.id, group.min_links).into());
+            }
+            for member in &group.members {
+                let port = check_port(member)?;
+                if let Some(owner) = owners.insert(*member, group.id) {
+                    return Err(LagError::PortAlreadyAggregated(*member, owner).into());
+                }
+                if let Some(expected) = group.speed.filter(|speed| *speed != port.speed) {
+                    return Err(ConfigError::SpeedMismatch {
+                        port: *member,
+                        speed: port.speed,
+                        expected,
+                    });
+                }
+            }
+            if let Some(mac) = &group.mac {
+                check_mac(mac)?;
+                if !macs.insert(*mac) {
+                    return Err(ConfigError::DuplicateMac(*mac));
+                }
+            }
+        }
+
+        let mut ports = BTreeSet::new();
+        for port in &self.ports {
+            check_port(&port.port)?;
+            if !ports.insert(port.port) {
+                return Err(ConfigError::DuplicatePort(port.port));
+            }
+            if let Some(mac) = &port.mac {
+                check_mac(mac)?;
+                if let Some(group) = owners
+                    .get(&port.port)
+                    .and_then(|id| self.groups.iter().find(|group| group.id == *id))
+                    .filter(|group| group.mac.is_some())
+                {
+                    return Err(ConfigError::MacConflict(port.port, group.id));
+                }
+                if !macs.insert(*mac) {
+                    return Err(ConfigError::DuplicateMac(*mac));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Changes turning the current `lag` groups and `programmed` MACs into
+    /// this configuration. Groups missing from the configuration are
+    /// deleted; MACs missing from it are left as they are.
+    pub fn diff(&self, lag: &LagManager, programmed: &HashMap<PhyPortId, MacAddr>) -> ConfigDiff {
+        let wanted: BTreeMap<GroupId, &GroupConfig> =
+            self.groups.iter().map(|group| (group.id, group)).collect();
+        let mut removals = Vec::new();
+        let mut additions = Vec::new();
+        let mut settings = Vec::new();
+
+        for group in lag.groups() {
+            match wanted.get(&group.id()) {
+                None => removals.push(ConfigChange::DeleteGroup(group.id())),
+                Some(config) => removals.extend(
+                    group
+                        .members()
+                        .iter()
+                        .filter(|port| !config.members.contains(port))
+                        .map(|port| ConfigChange::RemoveMember(group.id(), *port)),
+                ),
+            }
+        }
+
+        let default = GroupConfig::new(0);
+        for config in &self.groups {
+            let id = config.id;
+            let current = lag.group(id);
+            if current.is_none() {
+                additions.push(ConfigChange::CreateGroup(id));
+            }
+            additions.extend(
+                config
+                    .members
+                    .iter()
+                    .filter(|port| !current.is_some_and(|group| group.contains(port)))
+                    .map(|port| ConfigChange::AddMember(id, *port)),
+            );
+
+            let hash_policy = current.map_or(default.hash_policy, |group| group.hash_policy());
+            if config.hash_policy != hash_policy {
+                settings.push(ConfigChange::SetHashPolicy(id, config.hash_policy));
+            }
+            let min_links = current.map_or(default.min_links, |group| group.min_links());
+            if config.min_links != min_links {
+                settings.push(ConfigChange::SetMinLinks(id, config.min_links));
+            }
+            if config.speed != current.and_then(|group| group.speed()) {
+                settings.push(ConfigChange::SetGroupSpeed(id, config.speed));
+            }
+            if config.mac != current.and_then(|group| group.mac()) {
+                settings.push(ConfigChange::SetGroupMac(id, config.mac));
+            }
+        }
+
+        // Ports left out of the configuration go back to the default.
+        let priorities: BTreeMap<PhyPortId, u16> = self
+            .ports
+            .iter()
+            .filter_map(|port| Some((port.port, port.priority?)))
+            .collect();
+        let mut ports: BTreeSet<PhyPortId> = lag.port_priorities().map(|(port, _)| *port).collect();
+        ports.extend(priorities.keys());
+        for port in ports {
+            let priority = priorities
+                .get(&port)
+                .copied()
+                .unwrap_or(DEFAULT_PORT_PRIORITY);
+            if lag.port_priority(&port) != priority {
+                settings.push(ConfigChange::SetPortPriority(port, priority));
+            }
+        }
+        for (port, mac) in self.port_macs() {
+            if programmed.get(&port) != Some(&mac) {
+                settings.push(ConfigChange::SetMac(port, mac));
+            }
+        }
+
+        let mut changes = removals;
+        changes.extend(additions);
+        changes.extend(settings);
+        ConfigDiff { changes }
+    }
+
+    /// Validates the configuration against the device topology and applies
+    /// it, returning the changes made. With `dry_run` nothing is changed.
+    pub fn apply<S: ChipSdk>(
+        &self,
+        device: &Device<S>,
+        lag: &mut LagManager,
+        dry_run: bool,
+    ) -> ConfigResult<ConfigDiff> {
+        self.validate(&device.topology())?;
+        let diff = self.diff(lag, &device.programmed_macs());
+        if !dry_run {
+            diff.apply(device, lag)?;
+        }
+        Ok(diff)
+    }
+}
@@ -0,0 +1,351 @@
+use super::topology::Topology;
+use crate::ffi::*;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// How long a port must stay in a new state before the change is delivered.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct HoldTimers {
+    pub up: Duration,
+    pub down: Duration,
+}
+
+impl HoldTimers {
+    pub fn new(up: Duration, down: Duration) -> Self {
+        HoldTimers { up, down }
+    }
+
+    fn hold(&self, status: LinkStatus) -> Duration {
+        match status {
+            LinkStatus::LINK_UP => self.up,
+            LinkStatus::LINK_DOWN => self.down,
+        }
+    }
+}
+
+/// Exponential penalty dampening. Every time a port goes down it earns
+/// `penalty`, which halves every `half_life`. A port whose penalty reaches
+/// `suppress_threshold` is held down until the penalty decays below
+/// `reuse_threshold`, at most `max_suppress` after its last flap.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DampeningPolicy {
+    pub penalty: u32,
+    pub suppress_threshold: u32,
+    pub reuse_threshold: u32,
+    pub half_life: Duration,
+    pub max_suppress: Duration,
+}
+
+impl Default for DampeningPolicy {
+    fn default() -> Self {
+        DampeningPolicy {
+            penalty: 1000,
+            suppress_threshold: 2000,
+            reuse_threshold: 750,
+            half_life: Duration::from_secs(15),
+            max_suppress: Duration::from_secs(60),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DampeningError {
+    ZeroHalfLife,
+    ZeroReuseThreshold,
+    /// Ports must be released below the penalty that suppresses them.
+    ReuseNotBelowSuppress {
+        reuse: u32,
+        suppress: u32,
+    },
+}
+
+impl fmt::Display for DampeningError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DampeningError::ZeroHalfLife => write!(f, "Penalty half-life must not be zero"),
+            DampeningError::ZeroReuseThreshold => write!(f, "Reuse threshold must not be zero"),
+            DampeningError::ReuseNotBelowSuppress { reuse, suppress } => write!(
+                f,
+                "Reuse threshold {} must be below suppress threshold {}",
+                reuse, suppress
+            ),
+        }
+    }
+}
+
+impl Error for DampeningError {}
+
+pub type DampeningResult<T> = Result<T, DampeningError>;
+
+impl DampeningPolicy {
+    /// Checks that penalties decay and suppressed ports can be released.
+    pub fn validate(&self) -> DampeningResult<()> {
+        if self.half_life.is_zero() {
+            return Err(DampeningError::ZeroHalfLife);
+        }
+        if self.reuse_threshold == 0 {
+            return Err(DampeningError::ZeroReuseThreshold);
+        }
+        if self.reuse_threshold >= self.suppress_threshold {
+            return Err(DampeningError::ReuseNotBelowSuppress {
+                reuse: self.reuse_threshold,
+                suppress: self.suppress_threshold,
+            });
+        }
+        Ok(())
+    }
+
+    fn max_penalty(&self) -> f64 {
+        let half_lives = self.max_suppress.as_secs_f64() / self.half_life.as_secs_f64();
+        self.reuse_threshold as f64 * half_lives.exp2()
+    }
+
+    fn decay(&self, penalty: f64, elapsed: Duration) -> f64 {
+        penalty * (-elapsed.as_secs_f64() / self.half_life.as_secs_f64()).exp2()
+    }
+
+    /// Time for `penalty` to decay below the reuse threshold.
+    fn time_to_reuse(&self, penalty: f64) -> Duration {
+        let half_lives = (penalty / self.reuse_threshold as f64).log2().max(0.0);
+        self.half_life.mul_f64(half_lives)
+    }
+}
+
+/// Dampening state of a port.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DampingStatus {
+    /// Last status reported by the SDK.
+    pub reported: LinkStatus,
+    /// Last status delivered to subscribers.
+    pub stable: LinkStatus,
+    /// Status waiting for its hold timer.
+    pub pending: Option<LinkStatus>,
+    pub penalty: u32,
+    pub suppressed: bool,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct PortDamping {
+    reported: LinkStatus,
+    stable: LinkStatus,
+    pending: Option<(LinkStatus, Instant)>,
+    penalty: f64,
+    updated: Instant,
+    suppressed: bool,
+}
+
+impl PortDamping {
+    fn new(status: LinkStatus, now: Instant) -> Self {
+        PortDamping {
+            reported: status,
+            stable: status,
+            pending: None,
+            penalty: 0.0,
+            updated: now,
+            suppressed: false,
+        }
+    }
+}
+
+/// Filters raw link status reports so that only stable transitions get
+/// through. Time is passed in by the caller, like the LACP timers.
+///
+/// Without hold timers or a dampening policy every report passes through
+/// unchanged.
+#[derive(Debug, Default, Clone)]
+pub struct LinkDamper {
+    hold: HoldTimers,
+    port_hold: BTreeMap<PhyPortId, HoldTimers>,
+    policy: Option<DampeningPolicy>,
+    ports: BTreeMap<PhyPortId, PortDamping>,
+}
+
+impl LinkDamper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the hold timers of ports without their own.
+    pub fn set_hold_timers(&mut self, hold: HoldTimers) {
+        self.hold = hold;
+    }
+
+    pub fn set_port_hold_timers(&mut self, port: PhyPortId, hold: Option<HoldTimers>) {
+        match hold {
+            Some(hold) => self.port_hold.insert(port, hold),
+            None => self.port_hold.remove(&port),
+        };
+    }
+
+    pub fn hold_timers(&self, port: &PhyPortId) -> HoldTimers {
+        self.port_hold.get(port).copied().unwrap_or(self.hold)
+    }
+
+    /// Enables penalty dampening, or disables it with `None`. Suppressed
+    /// ports are released by the next `expire`. An invalid policy leaves
+    /// the current one in place.
+    pub fn set_policy(&mut self, policy: Option<DampeningPolicy>) -> DampeningResult<()> {
+        if let Some(policy) = &policy {
+            policy.validate()?;
+        }
+        self.policy = policy;
+        Ok(())
+    }
+
+    pub fn policy(&self) -> Option<&DampeningPolicy> {
+        self.policy.as_ref()
+    }
+
+    /// Starts tracking the ports of `topology` in their current state and
+    /// forgets ports that are no longer in it.
+    pub fn sync_topology(&mut self, topology: &Topology, now: Instant) {
+        self.ports.retain(|port, _| topology.contains(port));
+        for port in topology.ports() {
+            self.ports
+                .entry(port.id)
+                .or_insert_with(|| PortDamping::new(port.status, now));
+        }
+    }
+
+    /// Forgets the state of every port, keeping timers and policy.
+    pub fn reset(&mut self) {
+        self.ports.clear();
+    }
+
+    /// Feeds a raw report, returning the status to deliver right away.
+    /// Other changes are delivered by `expire` once their hold timer runs
+    /// out, or dropped if the port goes back before that.
+    pub fn report(
+        &mut self,
+        port: PhyPortId,
+        status: LinkStatus,
+        now: Instant,
+    ) -> Option<LinkStatus> {
+        let hold = self.hold_timers(&port).hold(status);
+        let policy = self.policy;
+        let damping = self
+            .ports
+            .entry(port)
+            .or_insert_with(|| PortDamping::new(LinkStatus::LINK_DOWN, now));
+
+        if let Some(policy) = policy {
+            damping.penalty = policy.decay(
+                damping.penalty,
+                now.saturating_duration_since(damping.updated),
+            );
+            damping.updated = now;
+            if status == LinkStatus::LINK_DOWN && damping.reported == LinkStatus::LINK_UP {
+                damping.penalty =
+                    (damping.penalty + policy.penalty as f64).min(policy.max_penalty());
+                if damping.penalty >= policy.suppress_threshold as f64 {
+                    damping.suppressed = true;
+                }
+            }
+        }
+        damping.reported = status;
+
+        if damping.suppressed {
+            damping.pending = None;
+            if damping.stable == LinkStatus::LINK_UP {
+                damping.stable = LinkStatus::LINK_DOWN;
+                return Some(LinkStatus::LINK_DOWN);
+            }
+            return None;
+        }
+        if status == damping.stable {
+            if damping.pending.take().is_some() {
+                return None;
+            }
+            return Some(status);
+        }
+        if hold.is_zero() {
+            damping.pending = None;
+            damping.stable = status;
+            return Some(status);
+        }
+        if damping.pending.is_none_or(|(pending, _)| pending != status) {
+            damping.pending = Some((status, now + hold));
+        }
+        None
+    }
+
+    /// Releases suppressed ports whose penalty decayed and returns the
+    /// changes whose hold timer ran out.
+    pub fn expire(&mut self, now: Instant) -> Vec<(PhyPortId, LinkStatus)> {
+        let mut changes = Vec::new();
+        for (port, damping) in self.ports.iter_mut() {
+            if damping.suppressed {
+                let released = self.policy.is_none_or(|policy| {
+                    damping.penalty = policy.decay(
+                        damping.penalty,
+                        now.saturating_duration_since(damping.updated),
+                    );
+                    damping.updated = now;
+                    damping.penalty < policy.reuse_threshold as f64
+                });
+                if !released {
+                    continue;
+                }
+                damping.suppressed = false;
+                if damping.reported != damping.stable {
+                    let hold = self
+                        .port_hold
+                        .get(port)
+                        .unwrap_or(&self.hold)
+                        .hold(damping.reported);
+                    damping.pending = Some((damping.reported, now + hold));
+                }
+            }
+            if let Some((status, deadline)) = damping.pending {
+                if now >= deadline {
+                    damping.pending = None;
+                    damping.stable = status;
+                    changes.push((*port, status));
+                }
+            }
+        }
+        changes
+    }
+
+    /// Earliest time at which `expire` has something to do.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.ports
+            .values()
+            .filter_map(|damping| match (damping.suppressed, self.policy) {
+                (true, Some(policy)) => {
+                    Some(damping.updated + policy.time_to_reuse(damping.penalty))
+                }
+                (true, None) => Some(damping.updated),
+                (false, _) => damping.pending.map(|(_, deadline)| deadline),
+            })
+            .min()
+    }
+
+    pub fn status(&self, port: &PhyPortId, now: Instant) -> Option<DampingStatus> {
+        let damping = self.ports.get(port)?;
+        let penalty = match self.policy {
+            Some(policy) => policy.decay(
+                damping.penalty,
+                now.saturating_duration_since(damping.updated),
+            ),
+            None => damping.penalty,
+        };
+        Some(DampingStatus {
+            reported: damping.reported,
+            stable: damping.stable,
+            pending: damping.pending.map(|(status, _)| status),
+            penalty: penalty as u32,
+            suppressed: damping.suppressed,
+        })
+    }
+
+    pub fn suppressed(&self) -> Vec<PhyPortId> {
+        self.ports
+            .iter()
+            .filter(|(_, damping)| damping.suppressed)
+            .map(|(port, _)| *port)
+            .collect()
+    }
+}
@@ -1,32 +1,500 @@
+use super::clock::Clock;
+use super::dampening::{DampeningPolicy, DampeningResult, DampingStatus, HoldTimers};
+use super::events::LinkEvents;
+use super::link::{LinkStatusHandler, LinkSubscription, LinkTimers, Subscribers};
+use super::mac::MacAddr;
+use super::retry::{Attempt, RetryPolicy};
+use super::topology::{Chip, Port, Topology, TopologyChange, TopologyError};
+use super::transaction::Transaction;
 use crate::ffi::*;
+use crate::sdk::{ChipSdk, FfiSdk, SdkOp};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
+use std::time::Instant;
 
-pub struct Device {
-    chips: [SwitchChip; CHIP_SDK_CHIP_MAX],
-    chip_num: i32,
+/// Lifecycle of a device.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DeviceState {
+    /// Not activated yet.
+    Created,
+    /// `activate` is initializing the SDK.
+    Activating,
+    Active,
+    /// Active, but the last SDK call still failed with a transient error
+    /// after its retries. The next successful call makes it active again.
+    Degraded,
+    /// Deactivated; `activate` may bring it back.
+    ShutDown,
 }
 
-impl Device {
+impl DeviceState {
+    fn is_active(&self) -> bool {
+        matches!(self, DeviceState::Active | DeviceState::Degraded)
+    }
+}
+
+impl fmt::Display for DeviceState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeviceState::Created => write!(f, "created"),
+            DeviceState::Activating => write!(f, "activating"),
+            DeviceState::Active => write!(f, "active"),
+            DeviceState::Degraded => write!(f, "degraded"),
+            DeviceState::ShutDown => write!(f, "shut down"),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DeviceError {
+    /// `operation` is not allowed while the device is in `state`.
+    InvalidState {
+        operation: &'static str,
+        state: DeviceState,
+    },
+    Topology(TopologyError),
+    /// Ports cannot use a multicast MAC.
+    InvalidMac(MacAddr),
+    Sdk(ChipSdkError),
+}
+
+impl fmt::Display for DeviceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeviceError::InvalidState { operation, state } => {
+                write!(f, "Cannot {} while the device is {}", operation, state)
+            }
+            DeviceError::Topology(err) => write!(f, "Invalid topology: {}", err),
+            DeviceError::InvalidMac(mac) => write!(f, "{} is not a unicast MAC", mac),
+            DeviceError::Sdk(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for DeviceError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DeviceError::Topology(err) => Some(err),
+            DeviceError::Sdk(err) => Some(err),
+            DeviceError::InvalidState { .. } | DeviceError::InvalidMac(_) => None,
+        }
+    }
+}
+
+impl From<ChipSdkError> for DeviceError {
+    fn from(err: ChipSdkError) -> Self {
+        DeviceError::Sdk(err)
+    }
+}
+
+pub type DeviceResult<T> = Result<T, DeviceError>;
+
+#[derive(Default)]
+struct State {
+    topology: Topology,
+    macs: HashMap<PhyPortId, MacAddr>,
+}
+
+struct Inner<S> {
+    sdk: S,
+    lifecycle: Mutex<DeviceState>,
+    /// Serializes SDK calls, which vendor SDKs do not expect to run
+    /// concurrently.
+    sdk_lock: Mutex<()>,
+    state: Arc<RwLock<State>>,
+    subscribers: Arc<Subscribers>,
+    retry: RwLock<RetryPolicy>,
+    attempts: Mutex<Vec<Attempt>>,
+}
+
+/// Handle of a switch device. Clones share the same device and may be used
+/// from any thread; link changes reported by the SDK update the topology
+/// before reaching subscribers.
+pub struct Device<S: ChipSdk = FfiSdk> {
+    inner: Arc<Inner<S>>,
+}
+
+impl<S: ChipSdk> Clone for Device<S> {
+    fn clone(&self) -> Self {
+        Device {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl Device<FfiSdk> {
     pub fn new() -> Self {
+        Self::with_sdk(FfiSdk)
+    }
+}
+
+impl<S: ChipSdk> Device<S> {
+    pub fn with_sdk(sdk: S) -> Self {
+        let state = Arc::new(RwLock::new(State::default()));
+        let observed = Arc::downgrade(&state);
+        let subscribers = Subscribers::with_observer(Box::new(move |port, status| {
+            if let Some(state) = observed.upgrade() {
+                state
+                    .write()
+                    .unwrap()
+                    .topology
+                    .set_link_status(&port, status);
+            }
+        }));
         Device {
-            chips: [SwitchChip::default(); CHIP_SDK_CHIP_MAX],
-            chip_num: 0,
+            inner: Arc::new(Inner {
+                sdk,
+                lifecycle: Mutex::new(DeviceState::Created),
+                sdk_lock: Mutex::new(()),
+                state,
+                subscribers: Arc::new(subscribers),
+                retry: RwLock::new(RetryPolicy::never()),
+                attempts: Mutex::default(),
+            }),
+        }
+    }
+
+    pub fn sdk(&self) -> &S {
+        &self.inner.sdk
+    }
+
+    /// Sets how SDK calls are retried. Devices make a single attempt by
+    /// default.
+    pub fn set_retry_policy(&self, retry: RetryPolicy) {
+        *self.inner.retry.write().unwrap() = retry;
+    }
+
+    pub fn retry_policy(&self) -> RetryPolicy {
+        *self.inner.retry.read().unwrap()
+    }
+
+    /// Attempts made by the last SDK operation.
+    pub fn last_attempts(&self) -> Vec<Attempt> {
+        self.inner.attempts.lock().unwrap().clone()
+    }
+
+    pub fn state(&self) -> DeviceState {
+        *self.inner.lifecycle.lock().unwrap()
+    }
+
+    fn require_active(&self, operation: &'static str) -> DeviceResult<()> {
+        let state = self.state();
+        if !state.is_active() {
+            return Err(DeviceError::InvalidState { operation, state });
+        }
+        Ok(())
+    }
+
+    /// Runs `call` with the retry policy, holding the SDK lock for each
+    /// attempt but not while backing off.
+    fn retry_locked(
+        &self,
+        op: SdkOp,
+        mut call: impl FnMut() -> SdkResult,
+    ) -> (SdkResult, Vec<Attempt>, MutexGuard<'_, ()>) {
+        self.retry_policy().run_with(op, || {
+            let sdk = self.inner.sdk_lock.lock().unwrap();
+            (call(), sdk)
+        })
+    }
+
+    /// Runs `call` with the retry policy while the device is active. A
+    /// transient error left after the retries degrades the device and a
+    /// success restores it. The SDK lock of the last attempt is returned so
+    /// that callers can record the outcome before other calls run.
+    fn call(
+        &self,
+        operation: &'static str,
+        op: SdkOp,
+        call: impl FnMut() -> SdkResult,
+    ) -> DeviceResult<MutexGuard<'_, ()>> {
+        self.require_active(operation)?;
+        let (result, attempts, sdk) = self.retry_locked(op, call);
+        *self.inner.attempts.lock().unwrap() = attempts;
+        let mut lifecycle = self.inner.lifecycle.lock().unwrap();
+        if !lifecycle.is_active() {
+            // Deactivated while backing off, which forgot the state the
+            // caller would record the outcome in.
+            return Err(DeviceError::InvalidState {
+                operation,
+                state: *lifecycle,
+            });
+        }
+        *lifecycle = match result {
+            Err(err) if err.is_transient() => DeviceState::Degraded,
+            _ => DeviceState::Active,
+        };
+        result?;
+        Ok(sdk)
+    }
+
+    /// Initializes the SDK, loads the topology it reports and registers for
+    /// link changes, which update the topology from then on whether or not
+    /// anyone subscribes. Allowed on a new or shut down device; on failure
+    /// the device keeps its state.
+    pub fn activate(&self) -> DeviceResult<()> {
+        let previous = {
+            let mut lifecycle = self.inner.lifecycle.lock().unwrap();
+            let state = *lifecycle;
+            if !matches!(state, DeviceState::Created | DeviceState::ShutDown) {
+                return Err(DeviceError::InvalidState {
+                    operation: "activate",
+                    state,
+                });
+            }
+            *lifecycle = DeviceState::Activating;
+            state
+        };
+        let result = self
+            .load_topology()
+            .and_then(|()| self.register_link_sink());
+        if result.is_err() {
+            self.clear();
+        }
+        *self.inner.lifecycle.lock().unwrap() = match result {
+            Ok(()) => DeviceState::Active,
+            Err(_) => previous,
+        };
+        result
+    }
+
+    /// Installs the handler the SDK reports link changes to. `activate`
+    /// does this; it is needed again when another device sharing the SDK,
+    /// which keeps a single handler, replaced it.
+    pub(crate) fn register_link_sink(&self) -> DeviceResult<()> {
+        let sink = self.inner.subscribers.sink();
+        let (result, attempts, _sdk) = self.retry_locked(SdkOp::SetLinkStatusHandler, || {
+            self.inner.sdk.set_link_status_handler(Arc::clone(&sink))
+        });
+        self.inner.attempts.lock().unwrap().extend(attempts);
+        Ok(result?)
+    }
+
+    /// Forgets the topology, the programmed MACs and the dampening state.
+    fn clear(&self) {
+        *self.inner.state.write().unwrap() = State::default();
+        self.inner
+            .subscribers
+            .damping
+            .lock()
+            .unwrap()
+            .damper
+            .reset();
+    }
+
+    fn load_topology(&self) -> DeviceResult<()> {
+        let mut chips = [SwitchChip::default(); CHIP_SDK_CHIP_MAX];
+        let mut chip_num = 0;
+        let (result, attempts, _sdk) = self.retry_locked(SdkOp::Init, || {
+            self.inner.sdk.init(&mut chips, &mut chip_num)
+        });
+        *self.inner.attempts.lock().unwrap() = attempts;
+        result?;
+        self.install_topology(Self::parse_topology(&chips, chip_num)?);
+        Ok(())
+    }
+
+    fn parse_topology(chips: &[SwitchChip], chip_num: i32) -> DeviceResult<Topology> {
+        let chip_num = usize::try_from(chip_num)
+            .map_err(|_| DeviceError::Sdk(ChipSdkError::CHIP_SDK_INVALID_PARAM))?;
+        let chips = chips
+            .get(..chip_num)
+            .ok_or(DeviceError::Topology(TopologyError::TooManyChips(chip_num)))?;
+        Topology::from_raw(chips).map_err(DeviceError::Topology)
+    }
+
+    /// Replaces the topology, forgetting the MACs and dampening state of
+    /// ports that are gone.
+    fn install_topology(&self, topology: Topology) {
+        let mut damping = self.inner.subscribers.damping.lock().unwrap();
+        let now = damping.clock.now();
+        damping.damper.sync_topology(&topology, now);
+        let mut state = self.inner.state.write().unwrap();
+        state.macs.retain(|port, _| topology.contains(port));
+        state.topology = topology;
+    }
+
+    /// Asks the SDK for the chips again and takes over the new topology,
+    /// returning how it differs from the previous one. MACs programmed on
+    /// removed ports are forgotten; ports coming back start without one.
+    pub fn rescan(&self) -> DeviceResult<Vec<TopologyChange>> {
+        let mut chips = [SwitchChip::default(); CHIP_SDK_CHIP_MAX];
+        let mut chip_num = 0;
+        let _sdk = self.call("rescan", SdkOp::Init, || {
+            self.inner.sdk.init(&mut chips, &mut chip_num)
+        })?;
+        let topology = Self::parse_topology(&chips, chip_num)?;
+        let changes = self.inner.state.read().unwrap().topology.diff(&topology);
+        self.install_topology(topology);
+        Ok(changes)
+    }
+
+    /// Shuts the device down: the SDK stops reporting link changes, current
+    /// subscriptions end, and the topology and programmed MACs are
+    /// forgotten. Hold timers and dampening settings are kept.
+    pub fn deactivate(&self) -> DeviceResult<()> {
+        let _sdk = self.inner.sdk_lock.lock().unwrap();
+        {
+            let mut lifecycle = self.inner.lifecycle.lock().unwrap();
+            if !lifecycle.is_active() {
+                return Err(DeviceError::InvalidState {
+                    operation: "deactivate",
+                    state: *lifecycle,
+                });
+            }
+            *lifecycle = DeviceState::ShutDown;
+        }
+        self.inner.subscribers.reset();
+        let result = self.inner.sdk.set_link_status_handler(Arc::new(|_, _| {}));
+        self.clear();
+        Ok(result?)
+    }
+
+    /// Copy of the current topology.
+    pub fn topology(&self) -> Topology {
+        self.inner.state.read().unwrap().topology.clone()
+    }
+
+    pub fn chips(&self) -> impl Iterator<Item = Chip> {
+        self.topology()
+            .chips()
+            .cloned()
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    pub fn ports(&self) -> impl Iterator<Item = Port> {
+        self.topology()
+            .ports()
+            .copied()
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    pub fn port(&self, phy_port_id: &PhyPortId) -> Option<Port> {
+        self.inner
+            .state
+            .read()
+            .unwrap()
+            .topology
+            .port(phy_port_id)
+            .copied()
+    }
+
+    pub fn subscribe_link_status(
+        &self,
+        handler: LinkStatusHandler,
+    ) -> DeviceResult<LinkSubscription> {
+        self.require_active("subscribe to link status")?;
+        Ok(self.inner.subscribers.subscribe(handler))
+    }
+
+    /// Subscribes a receiver buffering up to `capacity` events, at least
+    /// one.
+    pub fn link_events(&self, capacity: usize) -> DeviceResult<LinkEvents> {
+        let (sender, mut events) = LinkEvents::channel(capacity);
+        events.attach(self.subscribe_link_status(sender)?);
+        Ok(events)
+    }
+
+    /// Sets the clock measuring hold timers and penalty decay.
+    pub fn set_link_clock(&self, clock: impl Clock + Send + Sync + 'static) {
+        self.inner.subscribers.damping.lock().unwrap().clock = Arc::new(clock);
+    }
+
+    /// Sets the hold timers of every port without its own. Link changes
+    /// reach subscribers once the port stayed in the new state that long.
+    pub fn set_hold_timers(&self, hold: HoldTimers) {
+        let mut damping = self.inner.subscribers.damping.lock().unwrap();
+        damping.damper.set_hold_timers(hold);
+    }
+
+    /// Overrides the hold timers of one port, or restores the default with
+    /// `None`.
+    pub fn set_port_hold_timers(&self, phy_port_id: PhyPortId, hold: Option<HoldTimers>) {
+        let mut damping = self.inner.subscribers.damping.lock().unwrap();
+        damping.damper.set_port_hold_timers(phy_port_id, hold);
+    }
+
+    pub fn hold_timers(&self, phy_port_id: &PhyPortId) -> HoldTimers {
+        let damping = self.inner.subscribers.damping.lock().unwrap();
+        damping.damper.hold_timers(phy_port_id)
+    }
+
+    /// Enables penalty dampening of flapping ports, or disables it with
+    /// `None`.
+    pub fn set_dampening(&self, policy: Option<DampeningPolicy>) -> DampeningResult<()> {
+        let mut damping = self.inner.subscribers.damping.lock().unwrap();
+        damping.damper.set_policy(policy)
+    }
+
+    /// Delivers link changes whose hold timer ran out and releases ports
+    /// that are no longer suppressed, returning the number of changes.
+    /// Call it at `next_link_deadline`.
+    pub fn run_link_timers(&self) -> usize {
+        self.inner.subscribers.run_timers()
+    }
+
+    pub fn link_timers(&self) -> LinkTimers {
+        LinkTimers::new(&self.inner.subscribers)
+    }
+
+    pub fn next_link_deadline(&self) -> Option<Instant> {
+        let damping = self.inner.subscribers.damping.lock().unwrap();
+        damping.damper.next_deadline()
+    }
+
+    pub fn damping_status(&self, phy_port_id: &PhyPortId) -> Option<DampingStatus> {
+        let damping = self.inner.subscribers.damping.lock().unwrap();
+        damping.damper.status(phy_port_id, damping.clock.now())
+    }
+
+    /// Ports held down by dampening.
+    pub fn suppressed_ports(&self) -> Vec<PhyPortId> {
+        let damping = self.inner.subscribers.damping.lock().unwrap();
+        damping.damper.suppressed()
+    }
+
+    pub fn set_mac(&self, phy_port_id: &PhyPortId, mac: MacAddr) -> DeviceResult<()> {
+        if mac.is_multicast() {
+            return Err(DeviceError::InvalidMac(mac));
         }
+        let _sdk = self.call("set a MAC", SdkOp::SetMac, || {
+            self.inner.sdk.set_mac(phy_port_id, &mac.into())
+        })?;
+        self.inner
+            .state
+            .write()
+            .unwrap()
+            .macs
+            .insert(*phy_port_id, mac);
+        Ok(())
     }
 
-    pub fn activate(&mut self) -> SdkResult {
-        sdk_init(&mut self.chips, &mut self.chip_num)
+    /// Starts a batch of SDK operations that is undone if any of them fails.
+    pub fn transaction(&self) -> Transaction<'_, S> {
+        Transaction::new(self)
     }
 
-    pub fn register_link_status_callback(&self, cb: LinkStatusCallback) -> SdkResult {
-        sdk_register_link_status_callback(cb)
+    pub fn mac(&self, phy_port_id: &PhyPortId) -> Option<MacAddr> {
+        self.inner
+            .state
+            .read()
+            .unwrap()
+            .macs
+            .get(phy_port_id)
+            .copied()
     }
 
-    pub fn set_mac(&self, phy_port_id: &PhyPortId, mac: &Mac) -> SdkResult {
-        sdk_set_mac(phy_port_id, mac)
+    /// MAC addresses programmed through `set_mac`, by port.
+    pub fn programmed_macs(&self) -> HashMap<PhyPortId, MacAddr> {
+        self.inner.state.read().unwrap().macs.clone()
     }
 }
 
-impl Default for Device {
+impl Default for Device<FfiSdk> {
     fn default() -> Self {
         Self::new()
     }
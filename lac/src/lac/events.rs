@@ -0,0 +1,163 @@
+use super::link::{LinkStatusHandler, LinkSubscription};
+use crate::ffi::*;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::task::Waker;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LinkEvent {
+    pub port: PhyPortId,
+    pub status: LinkStatus,
+    pub timestamp: Instant,
+    /// Number of events dropped right before this one because the buffer
+    /// was full.
+    pub missed: u64,
+}
+
+#[derive(Default)]
+struct Shared {
+    pending_missed: AtomicU64,
+    total_missed: AtomicU64,
+    waker: Mutex<Option<Waker>>,
+}
+
+impl Shared {
+    fn wake(&self) {
+        if let Some(waker) = self.waker.lock().unwrap().take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Sending half of a `LinkEvents` channel, owned by its link status handler.
+struct EventSender {
+    sender: Option<SyncSender<LinkEvent>>,
+    shared: Arc<Shared>,
+}
+
+impl EventSender {
+    fn send(&self, port: PhyPortId, status: LinkStatus) {
+        let Some(sender) = &self.sender else {
+            return;
+        };
+        let shared = &self.shared;
+        let missed = shared.pending_missed.swap(0, Ordering::SeqCst);
+        let event = LinkEvent {
+            port,
+            status,
+            timestamp: Instant::now(),
+            missed,
+        };
+        match sender.try_send(event) {
+            Ok(()) => shared.wake(),
+            Err(TrySendError::Full(_)) => {
+                shared
+                    .pending_missed
+                    .fetch_add(missed + 1, Ordering::SeqCst);
+                shared.total_missed.fetch_add(1, Ordering::SeqCst);
+            }
+            Err(TrySendError::Disconnected(_)) => {}
+        }
+    }
+}
+
+impl Drop for EventSender {
+    /// Closes the channel before waking a pending stream, so that it sees
+    /// the end instead of waiting for events that no longer come.
+    fn drop(&mut self) {
+        drop(self.sender.take());
+        self.shared.wake();
+    }
+}
+
+/// Bounded receiver of link events. Events arriving while the buffer is full
+/// are dropped and reported through `LinkEvent::missed` and `missed()`.
+pub struct LinkEvents {
+    receiver: mpsc::Receiver<LinkEvent>,
+    shared: Arc<Shared>,
+    subscription: Option<LinkSubscription>,
+}
+
+impl LinkEvents {
+    /// A zero capacity is raised to one: a rendezvous channel would drop
+    /// every event, since the sender never waits.
+    pub(crate) fn channel(capacity: usize) -> (LinkStatusHandler, LinkEvents) {
+        let (sender, receiver) = mpsc::sync_channel(capacity.max(1));
+        let shared = Arc::new(Shared::default());
+        let sender = Self::sender(sender, Arc::clone(&shared));
+        let events = LinkEvents {
+            receiver,
+            shared,
+            subscription: None,
+        };
+        (sender, events)
+    }
+
+    pub(crate) fn attach(&mut self, subscription: LinkSubscription) {
+        self.subscription = Some(subscription);
+    }
+
+    fn sender(sender: SyncSender<LinkEvent>, shared: Arc<Shared>) -> LinkStatusHandler {
+        let sender = EventSender {
+            sender: Some(sender),
+            shared,
+        };
+        Box::new(move |port, status| sender.send(port, status))
+    }
+
+    pub fn recv(&self) -> Option<LinkEvent> {
+        self.receiver.recv().ok()
+    }
+
+    pub fn try_recv(&self) -> Option<LinkEvent> {
+        self.receiver.try_recv().ok()
+    }
+
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<LinkEvent> {
+        self.receiver.recv_timeout(timeout).ok()
+    }
+
+    pub fn try_iter(&self) -> impl Iterator<Item = LinkEvent> + '_ {
+        self.receiver.try_iter()
+    }
+
+    /// Total number of events dropped since the receiver was created.
+    pub fn missed(&self) -> u64 {
+        self.shared.total_missed.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for LinkEvents {
+    fn drop(&mut self) {
+        if let Some(subscription) = self.subscription.take() {
+            subscription.unsubscribe();
+        }
+    }
+}
+
+#[cfg(feature = "stream")]
+impl futures_core::Stream for LinkEvents {
+    type Item = LinkEvent;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<LinkEvent>> {
+        use std::sync::mpsc::TryRecvError;
+        use std::task::Poll;
+
+        match self.receiver.try_recv() {
+            Ok(event) => return Poll::Ready(Some(event)),
+            Err(TryRecvError::Disconnected) => return Poll::Ready(None),
+            Err(TryRecvError::Empty) => {}
+        }
+        *self.shared.waker.lock().unwrap() = Some(cx.waker().clone());
+        match self.receiver.try_recv() {
+            Ok(event) => Poll::Ready(Some(event)),
+            Err(TryRecvError::Disconnected) => Poll::Ready(None),
+            Err(TryRecvError::Empty) => Poll::Pending,
+        }
+    }
+}
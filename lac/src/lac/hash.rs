@@ -0,0 +1,173 @@
+use super::mac::MacAddr;
+use crate::ffi::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::IpAddr;
+
+/// Header fields of a flow that hash policies may use.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct FlowKey {
+    pub src_mac: MacAddr,
+    pub dst_mac: MacAddr,
+    pub src_ip: Option<IpAddr>,
+    pub dst_ip: Option<IpAddr>,
+    pub protocol: u8,
+    pub src_port: u16,
+    pub dst_port: u16,
+}
+
+/// Fields hashed to pick the egress member of a flow. Policies using IP
+/// fields fall back to `Layer2` for flows without addresses.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HashPolicy {
+    /// Source and destination MAC.
+    #[default]
+    #[serde(rename = "layer2")]
+    Layer2,
+    /// MACs plus source and destination IP.
+    #[serde(rename = "layer2+3")]
+    Layer2And3,
+    /// IPs, protocol and transport ports.
+    #[serde(rename = "layer3+4")]
+    Layer3And4,
+}
+
+impl fmt::Display for HashPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HashPolicy::Layer2 => write!(f, "layer2"),
+            HashPolicy::Layer2And3 => write!(f, "layer2+3"),
+            HashPolicy::Layer3And4 => write!(f, "layer3+4"),
+        }
+    }
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+#[derive(Copy, Clone)]
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Self {
+        Fnv(FNV_OFFSET)
+    }
+
+    fn write(mut self, bytes: &[u8]) -> Self {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+        self
+    }
+
+    /// Spreads the state over all bits (splitmix64 finalizer), as FNV
+    /// barely mixes the last bytes written.
+    fn finish(self) -> u64 {
+        let mut x = self.0;
+        x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        x ^ (x >> 31)
+    }
+
+    fn write_ip(self, ip: &IpAddr) -> Self {
+        match ip {
+            IpAddr::V4(ip) => self.write(&ip.octets()),
+            IpAddr::V6(ip) => self.write(&ip.octets()),
+        }
+    }
+}
+
+impl HashPolicy {
+    /// Stable hash of the fields of `flow` selected by this policy.
+    pub fn hash(&self, flow: &FlowKey) -> u64 {
+        let l2 = || {
+            Fnv::new()
+                .write(&flow.src_mac.octets())
+                .write(&flow.dst_mac.octets())
+        };
+        let (Some(src_ip), Some(dst_ip)) = (&flow.src_ip, &flow.dst_ip) else {
+            return l2().finish();
+        };
+        match self {
+            HashPolicy::Layer2 => l2().finish(),
+            HashPolicy::Layer2And3 => l2().write_ip(src_ip).write_ip(dst_ip).finish(),
+            HashPolicy::Layer3And4 => Fnv::new()
+                .write_ip(src_ip)
+                .write_ip(dst_ip)
+                .write(&[flow.protocol])
+                .write(&flow.src_port.to_be_bytes())
+                .write(&flow.dst_port.to_be_bytes())
+                .finish(),
+        }
+    }
+
+    /// Picks the member carrying `flow` by rendezvous hashing: each member
+    /// scores the flow and the highest score wins. Removing a member only
+    /// moves the flows it carried, and adding one back restores them.
+    pub fn select(&self, flow: &FlowKey, members: &[PhyPortId]) -> Option<PhyPortId> {
+        let hash = self.hash(flow).to_be_bytes();
+        members.iter().copied().max_by_key(|port| {
+            let score = Fnv::new()
+                .write(&hash)
+                .write(&port.0.to_be_bytes())
+                .write(&port.1.to_be_bytes())
+                .finish();
+            (score, *port)
+        })
+    }
+}
+
+/// Number of flows assigned to each member of a group.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FlowDistribution {
+    counts: BTreeMap<PhyPortId, usize>,
+    active: usize,
+    unassigned: usize,
+}
+
+impl FlowDistribution {
+    pub(crate) fn new(members: &[PhyPortId], active: usize) -> Self {
+        FlowDistribution {
+            counts: members.iter().map(|port| (*port, 0)).collect(),
+            active,
+            unassigned: 0,
+        }
+    }
+
+    pub(crate) fn record(&mut self, port: Option<PhyPortId>) {
+        match port {
+            Some(port) => *self.counts.entry(port).or_insert(0) += 1,
+            None => self.unassigned += 1,
+        }
+    }
+
+    pub fn count(&self, port: &PhyPortId) -> usize {
+        self.counts.get(port).copied().unwrap_or(0)
+    }
+
+    pub fn counts(&self) -> impl Iterator<Item = (&PhyPortId, &usize)> {
+        self.counts.iter()
+    }
+
+    /// Flows with no active member to carry them.
+    pub fn unassigned(&self) -> usize {
+        self.unassigned
+    }
+
+    pub fn total(&self) -> usize {
+        self.counts.values().sum::<usize>() + self.unassigned
+    }
+
+    /// Ratio of the busiest member's flows to the average over active
+    /// members, 1.0 being a perfect balance.
+    pub fn imbalance(&self) -> f64 {
+        let assigned: usize = self.counts.values().sum();
+        let max = self.counts.values().max().copied().unwrap_or(0);
+        if assigned == 0 {
+            return 1.0;
+        }
+        max as f64 * self.active as f64 / assigned as f64
+    }
+}
@@ -0,0 +1,58 @@
+use super::timer::Timer;
+use super::*;
+use std::time::Instant;
+
+/// State of the actor or partner churn detection machine.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ChurnState {
+    Monitor,
+    NoChurn,
+    Churn,
+}
+
+impl LacpPort {
+    pub(super) fn run_churn(&mut self, now: Instant) {
+        let enabled = self.port_enabled;
+        let actor_sync = self.actor.state.contains(LacpState::SYNCHRONIZATION);
+        let partner_sync = self.partner.state.contains(LacpState::SYNCHRONIZATION);
+        step_churn(
+            &mut self.actor_churn,
+            &mut self.actor_churn_timer,
+            enabled,
+            actor_sync,
+            now,
+        );
+        step_churn(
+            &mut self.partner_churn,
+            &mut self.partner_churn_timer,
+            enabled,
+            partner_sync,
+            now,
+        );
+    }
+}
+
+fn step_churn(state: &mut ChurnState, timer: &mut Timer, enabled: bool, sync: bool, now: Instant) {
+    if !enabled {
+        *state = ChurnState::Monitor;
+        timer.start(now, CHURN_DETECTION_TIME);
+        return;
+    }
+    match *state {
+        ChurnState::Monitor if !timer.running() => timer.start(now, CHURN_DETECTION_TIME),
+        ChurnState::Monitor if sync => {
+            *state = ChurnState::NoChurn;
+            timer.stop();
+        }
+        ChurnState::Monitor if timer.expired(now) => *state = ChurnState::Churn,
+        ChurnState::NoChurn if !sync => {
+            *state = ChurnState::Monitor;
+            timer.start(now, CHURN_DETECTION_TIME);
+        }
+        ChurnState::Churn if sync => {
+            *state = ChurnState::NoChurn;
+            timer.stop();
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,104 @@
+use super::selection::run_selection;
+use super::*;
+use crate::ffi::LinkStatus;
+use crate::lac::clock::Clock;
+use std::collections::{BTreeMap, HashMap};
+
+/// LACP actor of one system, running the protocol machines of every
+/// aggregatable port on each call to `run`.
+pub struct Lacp<C: Clock, F: FrameIo> {
+    system: SystemId,
+    clock: C,
+    io: F,
+    ports: BTreeMap<PhyPortId, LacpPort>,
+}
+
+impl<C: Clock, F: FrameIo> Lacp<C, F> {
+    pub fn new(system: SystemId, clock: C, io: F) -> Self {
+        Lacp {
+            system,
+            clock,
+            io,
+            ports: BTreeMap::new(),
+        }
+    }
+
+    pub fn system(&self) -> SystemId {
+        self.system
+    }
+
+    pub fn io(&self) -> &F {
+        &self.io
+    }
+
+    pub fn io_mut(&mut self) -> &mut F {
+        &mut self.io
+    }
+
+    pub fn add_port(&mut self, id: PhyPortId, config: LacpPortConfig) -> LacpResult<()> {
+        let port = LacpPort::new(id, self.system, &config)?;
+        self.ports.insert(id, port);
+        Ok(())
+    }
+
+    pub fn remove_port(&mut self, id: &PhyPortId) -> Option<LacpPort> {
+        self.ports.remove(id)
+    }
+
+    pub fn port(&self, id: &PhyPortId) -> Option<&LacpPort> {
+        self.ports.get(id)
+    }
+
+    pub fn ports(&self) -> impl Iterator<Item = &LacpPort> {
+        self.ports.values()
+    }
+
+    pub fn set_link_status(&mut self, id: &PhyPortId, status: LinkStatus) {
+        if let Some(port) = self.ports.get_mut(id) {
+            port.port_enabled = status == LinkStatus::LINK_UP;
+        }
+    }
+
+    pub fn distributing(&self, key: u16) -> Vec<PhyPortId> {
+        self.ports
+            .values()
+            .filter(|port| port.actor.key == key && port.is_distributing())
+            .map(|port| port.id)
+            .collect()
+    }
+
+    pub fn run(&mut self) {
+        let now = self.clock.now();
+
+        while let Some((id, pdu)) = self.io.receive() {
+            if let Some(port) = self.ports.get_mut(&id) {
+                port.rx_queue.push_back(pdu);
+            }
+        }
+
+        for port in self.ports.values_mut() {
+            port.run_receive(now);
+            port.run_periodic(now);
+        }
+
+        run_selection(&mut self.ports);
+
+        let mut ready: HashMap<u16, bool> = HashMap::new();
+        for port in self
+            .ports
+            .values()
+            .filter(|port| port.selected == Selection::Selected)
+        {
+            *ready.entry(port.actor.key).or_insert(true) &= port.waiting_ready(now);
+        }
+
+        for port in self.ports.values_mut() {
+            let ready = ready.get(&port.actor.key).copied().unwrap_or(false);
+            port.run_mux(now, ready);
+            port.run_churn(now);
+            if let Some(pdu) = port.run_transmit(now) {
+                self.io.transmit(port.id, &pdu);
+            }
+        }
+    }
+}
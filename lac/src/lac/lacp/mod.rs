@@ -0,0 +1,105 @@
+mod churn;
+mod engine;
+mod mux;
+pub mod pdu;
+mod periodic;
+mod port;
+mod receive;
+mod selection;
+mod timer;
+mod transmit;
+
+pub use churn::ChurnState;
+pub use engine::Lacp;
+pub use mux::MuxState;
+pub use pdu::{MarkerKind, MarkerPdu, PduError, PduResult, SlowFrame, SlowPdu};
+pub use periodic::PeriodicState;
+pub use port::{LacpActivity, LacpError, LacpPort, LacpPortConfig, LacpResult, LacpTimeout};
+pub use receive::RxState;
+pub use selection::Selection;
+
+use crate::ffi::PhyPortId;
+use std::time::Duration;
+
+pub const FAST_PERIODIC_TIME: Duration = Duration::from_secs(1);
+pub const SLOW_PERIODIC_TIME: Duration = Duration::from_secs(30);
+pub const SHORT_TIMEOUT_TIME: Duration = Duration::from_secs(3);
+pub const LONG_TIMEOUT_TIME: Duration = Duration::from_secs(90);
+pub const CHURN_DETECTION_TIME: Duration = Duration::from_secs(60);
+pub const AGGREGATE_WAIT_TIME: Duration = Duration::from_secs(2);
+pub const TX_LIMIT_PER_FAST_PERIOD: usize = 3;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct LacpState(u8);
+
+impl LacpState {
+    pub const ACTIVITY: u8 = 0x01;
+    pub const TIMEOUT: u8 = 0x02;
+    pub const AGGREGATION: u8 = 0x04;
+    pub const SYNCHRONIZATION: u8 = 0x08;
+    pub const COLLECTING: u8 = 0x10;
+    pub const DISTRIBUTING: u8 = 0x20;
+    pub const DEFAULTED: u8 = 0x40;
+    pub const EXPIRED: u8 = 0x80;
+
+    pub const fn from_bits(bits: u8) -> Self {
+        LacpState(bits)
+    }
+
+    pub const fn bits(&self) -> u8 {
+        self.0
+    }
+
+    pub const fn contains(&self, flag: u8) -> bool {
+        self.0 & flag == flag
+    }
+
+    pub fn set(&mut self, flag: u8, value: bool) {
+        if value {
+            self.0 |= flag;
+        } else {
+            self.0 &= !flag;
+        }
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SystemId {
+    pub priority: u16,
+    pub mac: [u8; 6],
+}
+
+/// Actor or partner information as carried in a LACPDU.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct LacpInfo {
+    pub system: SystemId,
+    pub key: u16,
+    pub port_priority: u16,
+    pub port: u16,
+    pub state: LacpState,
+}
+
+impl LacpInfo {
+    /// Compares the parameters that identify a link, ignoring the state bits
+    /// other than aggregation.
+    pub fn same_link(&self, other: &LacpInfo) -> bool {
+        self.system == other.system
+            && self.key == other.key
+            && self.port_priority == other.port_priority
+            && self.port == other.port
+            && self.state.contains(LacpState::AGGREGATION)
+                == other.state.contains(LacpState::AGGREGATION)
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Lacpdu {
+    pub actor: LacpInfo,
+    pub partner: LacpInfo,
+    pub collector_max_delay: u16,
+}
+
+pub trait FrameIo {
+    fn transmit(&mut self, port: PhyPortId, pdu: &Lacpdu);
+    fn receive(&mut self) -> Option<(PhyPortId, Lacpdu)>;
+}
@@ -0,0 +1,71 @@
+use super::*;
+use std::time::Instant;
+
+/// Mux machine states with independent control of collecting and
+/// distributing.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MuxState {
+    Detached,
+    Waiting,
+    Attached,
+    Collecting,
+    Distributing,
+}
+
+impl LacpPort {
+    pub(super) fn run_mux(&mut self, now: Instant, ready: bool) {
+        while let Some(next) = self.next_mux_state(ready) {
+            self.enter_mux(next, now);
+        }
+    }
+
+    pub(super) fn waiting_ready(&self, now: Instant) -> bool {
+        self.mux_state != MuxState::Waiting || self.wait_while.expired(now)
+    }
+
+    fn next_mux_state(&self, ready: bool) -> Option<MuxState> {
+        let selected = self.selected == Selection::Selected;
+        let partner_sync = self.partner.state.contains(LacpState::SYNCHRONIZATION);
+        let partner_collecting = self.partner.state.contains(LacpState::COLLECTING);
+        match self.mux_state {
+            MuxState::Detached if self.selected != Selection::Unselected => Some(MuxState::Waiting),
+            MuxState::Waiting if self.selected == Selection::Unselected => Some(MuxState::Detached),
+            MuxState::Waiting if selected && ready => Some(MuxState::Attached),
+            MuxState::Attached if !selected => Some(MuxState::Detached),
+            MuxState::Attached if partner_sync => Some(MuxState::Collecting),
+            MuxState::Collecting if !selected || !partner_sync => Some(MuxState::Attached),
+            MuxState::Collecting if partner_collecting => Some(MuxState::Distributing),
+            MuxState::Distributing if !selected || !partner_sync || !partner_collecting => {
+                Some(MuxState::Collecting)
+            }
+            _ => None,
+        }
+    }
+
+    fn enter_mux(&mut self, state: MuxState, now: Instant) {
+        self.mux_state = state;
+        match state {
+            MuxState::Detached => {
+                self.actor.state.set(LacpState::SYNCHRONIZATION, false);
+                self.actor.state.set(LacpState::COLLECTING, false);
+                self.actor.state.set(LacpState::DISTRIBUTING, false);
+                self.wait_while.stop();
+                self.ntt = true;
+            }
+            MuxState::Waiting => self.wait_while.start(now, AGGREGATE_WAIT_TIME),
+            MuxState::Attached => {
+                self.actor.state.set(LacpState::SYNCHRONIZATION, true);
+                self.actor.state.set(LacpState::COLLECTING, false);
+                self.ntt = true;
+            }
+            MuxState::Collecting => {
+                self.actor.state.set(LacpState::COLLECTING, true);
+                self.actor.state.set(LacpState::DISTRIBUTING, false);
+                self.ntt = true;
+            }
+            MuxState::Distributing => {
+                self.actor.state.set(LacpState::DISTRIBUTING, true);
+            }
+        }
+    }
+}
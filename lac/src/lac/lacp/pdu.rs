@@ -0,0 +1,318 @@
+use super::*;
+use std::error::Error;
+use std::fmt;
+
+pub const SLOW_PROTOCOLS_MAC: [u8; 6] = [0x01, 0x80, 0xc2, 0x00, 0x00, 0x02];
+pub const SLOW_PROTOCOLS_ETHERTYPE: u16 = 0x8809;
+pub const LACP_SUBTYPE: u8 = 0x01;
+pub const MARKER_SUBTYPE: u8 = 0x02;
+pub const PDU_VERSION: u8 = 0x01;
+
+const ETHERNET_HEADER_LEN: usize = 14;
+const PDU_LEN: usize = 110;
+pub const FRAME_LEN: usize = ETHERNET_HEADER_LEN + PDU_LEN;
+
+const TLV_TERMINATOR: u8 = 0x00;
+const TLV_ACTOR: u8 = 0x01;
+const TLV_PARTNER: u8 = 0x02;
+const TLV_COLLECTOR: u8 = 0x03;
+const TLV_MARKER: u8 = 0x01;
+const TLV_MARKER_RESPONSE: u8 = 0x02;
+
+const INFO_TLV_LEN: u8 = 20;
+const COLLECTOR_TLV_LEN: u8 = 16;
+const MARKER_TLV_LEN: u8 = 16;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MarkerKind {
+    Request,
+    Response,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MarkerPdu {
+    pub kind: MarkerKind,
+    pub requester_port: u16,
+    pub requester_system: [u8; 6],
+    pub transaction_id: u32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SlowPdu {
+    Lacp(Lacpdu),
+    Marker(MarkerPdu),
+}
+
+/// A Slow Protocols Ethernet frame without FCS.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SlowFrame {
+    pub src: [u8; 6],
+    pub pdu: SlowPdu,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PduError {
+    Truncated { expected: usize, actual: usize },
+    InvalidDestination([u8; 6]),
+    InvalidEtherType(u16),
+    UnsupportedSubtype(u8),
+    UnsupportedVersion(u8),
+    UnexpectedTlv { expected: u8, actual: u8 },
+    InvalidTlvLength { tlv: u8, length: u8 },
+}
+
+impl fmt::Display for PduError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PduError::Truncated { expected, actual } => {
+                write!(
+                    f,
+                    "Frame truncated: expected {} bytes, got {}",
+                    expected, actual
+                )
+            }
+            PduError::InvalidDestination(mac) => {
+                write!(f, "Invalid destination address {:02x?}", mac)
+            }
+            PduError::InvalidEtherType(ether_type) => {
+                write!(f, "Invalid ethertype {:#06x}", ether_type)
+            }
+            PduError::UnsupportedSubtype(subtype) => {
+                write!(f, "Unsupported slow protocol subtype {:#04x}", subtype)
+            }
+            PduError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported version {}", version)
+            }
+            PduError::UnexpectedTlv { expected, actual } => {
+                write!(
+                    f,
+                    "Expected TLV type {:#04x}, got {:#04x}",
+                    expected, actual
+                )
+            }
+            PduError::InvalidTlvLength { tlv, length } => {
+                write!(f, "Invalid length {} for TLV type {:#04x}", length, tlv)
+            }
+        }
+    }
+}
+
+impl Error for PduError {}
+
+pub type PduResult<T> = Result<T, PduError>;
+
+impl SlowFrame {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = Writer(Vec::with_capacity(FRAME_LEN));
+        writer.bytes(&SLOW_PROTOCOLS_MAC);
+        writer.bytes(&self.src);
+        writer.u16(SLOW_PROTOCOLS_ETHERTYPE);
+        match &self.pdu {
+            SlowPdu::Lacp(pdu) => encode_lacpdu(&mut writer, pdu),
+            SlowPdu::Marker(pdu) => encode_marker(&mut writer, pdu),
+        }
+        writer.0.resize(FRAME_LEN, 0);
+        writer.0
+    }
+
+    pub fn decode(frame: &[u8]) -> PduResult<SlowFrame> {
+        if frame.len() < FRAME_LEN {
+            return Err(PduError::Truncated {
+                expected: FRAME_LEN,
+                actual: frame.len(),
+            });
+        }
+        let mut reader = Reader(frame);
+        let dst = reader.array();
+        if dst != SLOW_PROTOCOLS_MAC {
+            return Err(PduError::InvalidDestination(dst));
+        }
+        let src = reader.array();
+        let ether_type = reader.u16();
+        if ether_type != SLOW_PROTOCOLS_ETHERTYPE {
+            return Err(PduError::InvalidEtherType(ether_type));
+        }
+        let subtype = reader.u8();
+        let version = reader.u8();
+        if subtype != LACP_SUBTYPE && subtype != MARKER_SUBTYPE {
+            return Err(PduError::UnsupportedSubtype(subtype));
+        }
+        if version != PDU_VERSION {
+            return Err(PduError::UnsupportedVersion(version));
+        }
+        let pdu = if subtype == LACP_SUBTYPE {
+            SlowPdu::Lacp(decode_lacpdu(&mut reader)?)
+        } else {
+            SlowPdu::Marker(decode_marker(&mut reader)?)
+        };
+        Ok(SlowFrame { src, pdu })
+    }
+}
+
+fn encode_lacpdu(writer: &mut Writer, pdu: &Lacpdu) {
+    writer.u8(LACP_SUBTYPE);
+    writer.u8(PDU_VERSION);
+    encode_info(writer, TLV_ACTOR, &pdu.actor);
+    encode_info(writer, TLV_PARTNER, &pdu.partner);
+    writer.tlv_header(TLV_COLLECTOR, COLLECTOR_TLV_LEN);
+    writer.u16(pdu.collector_max_delay);
+    writer.zeros(12);
+    writer.tlv_header(TLV_TERMINATOR, 0);
+}
+
+fn encode_info(writer: &mut Writer, tlv: u8, info: &LacpInfo) {
+    writer.tlv_header(tlv, INFO_TLV_LEN);
+    writer.u16(info.system.priority);
+    writer.bytes(&info.system.mac);
+    writer.u16(info.key);
+    writer.u16(info.port_priority);
+    writer.u16(info.port);
+    writer.u8(info.state.bits());
+    writer.zeros(3);
+}
+
+fn encode_marker(writer: &mut Writer, pdu: &MarkerPdu) {
+    writer.u8(MARKER_SUBTYPE);
+    writer.u8(PDU_VERSION);
+    let tlv = match pdu.kind {
+        MarkerKind::Request => TLV_MARKER,
+        MarkerKind::Response => TLV_MARKER_RESPONSE,
+    };
+    writer.tlv_header(tlv, MARKER_TLV_LEN);
+    writer.u16(pdu.requester_port);
+    writer.bytes(&pdu.requester_system);
+    writer.u32(pdu.transaction_id);
+    writer.zeros(2);
+    writer.tlv_header(TLV_TERMINATOR, 0);
+}
+
+fn decode_lacpdu(reader: &mut Reader) -> PduResult<Lacpdu> {
+    let actor = decode_info(reader, TLV_ACTOR)?;
+    let partner = decode_info(reader, TLV_PARTNER)?;
+    reader.tlv_header(TLV_COLLECTOR, COLLECTOR_TLV_LEN)?;
+    let collector_max_delay = reader.u16();
+    reader.skip(12);
+    reader.tlv_header(TLV_TERMINATOR, 0)?;
+    Ok(Lacpdu {
+        actor,
+        partner,
+        collector_max_delay,
+    })
+}
+
+fn decode_info(reader: &mut Reader, tlv: u8) -> PduResult<LacpInfo> {
+    reader.tlv_header(tlv, INFO_TLV_LEN)?;
+    let info = LacpInfo {
+        system: SystemId {
+            priority: reader.u16(),
+            mac: reader.array(),
+        },
+        key: reader.u16(),
+        port_priority: reader.u16(),
+        port: reader.u16(),
+        state: LacpState::from_bits(reader.u8()),
+    };
+    reader.skip(3);
+    Ok(info)
+}
+
+fn decode_marker(reader: &mut Reader) -> PduResult<MarkerPdu> {
+    let kind = match reader.peek() {
+        TLV_MARKER => MarkerKind::Request,
+        TLV_MARKER_RESPONSE => MarkerKind::Response,
+        actual => {
+            return Err(PduError::UnexpectedTlv {
+                expected: TLV_MARKER,
+                actual,
+            })
+        }
+    };
+    reader.tlv_header(reader.peek(), MARKER_TLV_LEN)?;
+    let pdu = MarkerPdu {
+        kind,
+        requester_port: reader.u16(),
+        requester_system: reader.array(),
+        transaction_id: reader.u32(),
+    };
+    reader.skip(2);
+    reader.tlv_header(TLV_TERMINATOR, 0)?;
+    Ok(pdu)
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.0.extend_from_slice(&value.to_be_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_be_bytes());
+    }
+
+    fn bytes(&mut self, value: &[u8]) {
+        self.0.extend_from_slice(value);
+    }
+
+    fn zeros(&mut self, len: usize) {
+        self.0.resize(self.0.len() + len, 0);
+    }
+
+    fn tlv_header(&mut self, tlv: u8, length: u8) {
+        self.u8(tlv);
+        self.u8(length);
+    }
+}
+
+/// Reads fields from a frame whose length has already been checked against
+/// `FRAME_LEN`.
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn peek(&self) -> u8 {
+        self.0[0]
+    }
+
+    fn u8(&mut self) -> u8 {
+        let value = self.0[0];
+        self.0 = &self.0[1..];
+        value
+    }
+
+    fn u16(&mut self) -> u16 {
+        u16::from_be_bytes(self.array())
+    }
+
+    fn u32(&mut self) -> u32 {
+        u32::from_be_bytes(self.array())
+    }
+
+    fn array<const N: usize>(&mut self) -> [u8; N] {
+        let (value, rest) = self.0.split_at(N);
+        self.0 = rest;
+        value.try_into().unwrap()
+    }
+
+    fn skip(&mut self, len: usize) {
+        self.0 = &self.0[len..];
+    }
+
+    fn tlv_header(&mut self, expected: u8, expected_len: u8) -> PduResult<()> {
+        let tlv = self.u8();
+        let length = self.u8();
+        if tlv != expected {
+            return Err(PduError::UnexpectedTlv {
+                expected,
+                actual: tlv,
+            });
+        }
+        if length != expected_len {
+            return Err(PduError::InvalidTlvLength { tlv, length });
+        }
+        Ok(())
+    }
+}
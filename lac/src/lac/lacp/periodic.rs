@@ -0,0 +1,50 @@
+use super::*;
+use std::time::Instant;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PeriodicState {
+    NoPeriodic,
+    FastPeriodic,
+    SlowPeriodic,
+    PeriodicTx,
+}
+
+impl LacpPort {
+    pub(super) fn run_periodic(&mut self, now: Instant) {
+        let both_passive = !self.actor.state.contains(LacpState::ACTIVITY)
+            && !self.partner.state.contains(LacpState::ACTIVITY);
+        if !self.port_enabled || !self.lacp_enabled || both_passive {
+            self.periodic_state = PeriodicState::NoPeriodic;
+            self.periodic_timer.stop();
+            return;
+        }
+
+        while let Some(next) = self.next_periodic_state(now) {
+            self.enter_periodic(next, now);
+        }
+    }
+
+    fn next_periodic_state(&self, now: Instant) -> Option<PeriodicState> {
+        let short = self.partner_is_short_timeout();
+        let expired = self.periodic_timer.expired(now);
+        match self.periodic_state {
+            PeriodicState::NoPeriodic => Some(PeriodicState::FastPeriodic),
+            PeriodicState::FastPeriodic if !short => Some(PeriodicState::SlowPeriodic),
+            PeriodicState::FastPeriodic if expired => Some(PeriodicState::PeriodicTx),
+            PeriodicState::SlowPeriodic if expired || short => Some(PeriodicState::PeriodicTx),
+            PeriodicState::PeriodicTx if short => Some(PeriodicState::FastPeriodic),
+            PeriodicState::PeriodicTx => Some(PeriodicState::SlowPeriodic),
+            _ => None,
+        }
+    }
+
+    fn enter_periodic(&mut self, state: PeriodicState, now: Instant) {
+        self.periodic_state = state;
+        match state {
+            PeriodicState::NoPeriodic => self.periodic_timer.stop(),
+            PeriodicState::FastPeriodic => self.periodic_timer.start(now, FAST_PERIODIC_TIME),
+            PeriodicState::SlowPeriodic => self.periodic_timer.start(now, SLOW_PERIODIC_TIME),
+            PeriodicState::PeriodicTx => self.ntt = true,
+        }
+    }
+}
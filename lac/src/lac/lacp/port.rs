@@ -0,0 +1,192 @@
+use super::timer::Timer;
+use super::*;
+use crate::ffi::CHIP_SDK_PHY_PORT_PER_CHIP;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::time::Instant;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum LacpActivity {
+    #[default]
+    Active,
+    Passive,
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum LacpTimeout {
+    Short,
+    #[default]
+    Long,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LacpError {
+    /// The port has no LACP port number: its ids are negative, the port id
+    /// is past the ports of a chip, or the number does not fit in 16 bits.
+    InvalidPort(PhyPortId),
+}
+
+impl fmt::Display for LacpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LacpError::InvalidPort(port) => write!(f, "Port {} has no LACP port number", port),
+        }
+    }
+}
+
+impl Error for LacpError {}
+
+pub type LacpResult<T> = Result<T, LacpError>;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LacpPortConfig {
+    pub key: u16,
+    pub port_priority: u16,
+    pub activity: LacpActivity,
+    pub timeout: LacpTimeout,
+}
+
+impl Default for LacpPortConfig {
+    fn default() -> Self {
+        LacpPortConfig {
+            key: 0,
+            port_priority: 0x8000,
+            activity: LacpActivity::default(),
+            timeout: LacpTimeout::default(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct LacpPort {
+    pub(super) id: PhyPortId,
+    pub(super) actor: LacpInfo,
+    pub(super) partner: LacpInfo,
+    pub(super) partner_admin: LacpInfo,
+    pub(super) port_enabled: bool,
+    pub(super) lacp_enabled: bool,
+    pub(super) selected: Selection,
+    pub(super) ntt: bool,
+    pub(super) port_moved: bool,
+    pub(super) rx_state: RxState,
+    pub(super) rx_queue: VecDeque<Lacpdu>,
+    pub(super) current_while: Timer,
+    pub(super) periodic_state: PeriodicState,
+    pub(super) periodic_timer: Timer,
+    pub(super) mux_state: MuxState,
+    pub(super) wait_while: Timer,
+    pub(super) actor_churn: ChurnState,
+    pub(super) actor_churn_timer: Timer,
+    pub(super) partner_churn: ChurnState,
+    pub(super) partner_churn_timer: Timer,
+    pub(super) tx_history: VecDeque<Instant>,
+}
+
+impl LacpPort {
+    pub(super) fn new(
+        id: PhyPortId,
+        system: SystemId,
+        config: &LacpPortConfig,
+    ) -> LacpResult<Self> {
+        let port = port_number(&id).ok_or(LacpError::InvalidPort(id))?;
+        let mut state = LacpState::default();
+        state.set(LacpState::ACTIVITY, config.activity == LacpActivity::Active);
+        state.set(LacpState::TIMEOUT, config.timeout == LacpTimeout::Short);
+        state.set(LacpState::AGGREGATION, true);
+
+        let mut partner_admin = LacpInfo::default();
+        partner_admin.state.set(LacpState::AGGREGATION, true);
+
+        Ok(LacpPort {
+            id,
+            actor: LacpInfo {
+                system,
+                key: config.key,
+                port_priority: config.port_priority,
+                port,
+                state,
+            },
+            partner: partner_admin,
+            partner_admin,
+            port_enabled: false,
+            lacp_enabled: true,
+            selected: Selection::Unselected,
+            ntt: false,
+            port_moved: false,
+            rx_state: RxState::Initialize,
+            rx_queue: VecDeque::new(),
+            current_while: Timer::default(),
+            periodic_state: PeriodicState::NoPeriodic,
+            periodic_timer: Timer::default(),
+            mux_state: MuxState::Detached,
+            wait_while: Timer::default(),
+            actor_churn: ChurnState::Monitor,
+            actor_churn_timer: Timer::default(),
+            partner_churn: ChurnState::Monitor,
+            partner_churn_timer: Timer::default(),
+            tx_history: VecDeque::new(),
+        })
+    }
+
+    pub fn id(&self) -> PhyPortId {
+        self.id
+    }
+
+    pub fn actor(&self) -> &LacpInfo {
+        &self.actor
+    }
+
+    pub fn partner(&self) -> &LacpInfo {
+        &self.partner
+    }
+
+    pub fn selected(&self) -> Selection {
+        self.selected
+    }
+
+    pub fn rx_state(&self) -> RxState {
+        self.rx_state
+    }
+
+    pub fn periodic_state(&self) -> PeriodicState {
+        self.periodic_state
+    }
+
+    pub fn mux_state(&self) -> MuxState {
+        self.mux_state
+    }
+
+    pub fn actor_churn(&self) -> bool {
+        self.actor_churn == ChurnState::Churn
+    }
+
+    pub fn partner_churn(&self) -> bool {
+        self.partner_churn == ChurnState::Churn
+    }
+
+    pub fn is_distributing(&self) -> bool {
+        self.mux_state == MuxState::Distributing
+    }
+
+    pub(super) fn partner_is_short_timeout(&self) -> bool {
+        self.partner.state.contains(LacpState::TIMEOUT)
+    }
+
+    pub(super) fn is_aggregatable(&self) -> bool {
+        self.actor.state.contains(LacpState::AGGREGATION)
+            && self.partner.state.contains(LacpState::AGGREGATION)
+    }
+}
+
+/// Numbers the ports of each chip after those of the previous one, from 1.
+fn port_number(id: &PhyPortId) -> Option<u16> {
+    let chip = usize::try_from(id.0).ok()?;
+    let port = usize::try_from(id.1)
+        .ok()
+        .filter(|port| *port < CHIP_SDK_PHY_PORT_PER_CHIP)?;
+    let number = chip
+        .checked_mul(CHIP_SDK_PHY_PORT_PER_CHIP)?
+        .checked_add(port + 1)?;
+    u16::try_from(number).ok()
+}
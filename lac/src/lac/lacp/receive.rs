@@ -0,0 +1,162 @@
+use super::*;
+use std::time::Instant;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RxState {
+    Initialize,
+    PortDisabled,
+    Expired,
+    LacpDisabled,
+    Defaulted,
+    Current,
+}
+
+/// Transition of the receive machine. `Current` is entered on a LACPDU,
+/// which it records.
+#[derive(Debug, Copy, Clone)]
+enum RxEntry<'a> {
+    Initialize,
+    PortDisabled,
+    Expired,
+    LacpDisabled,
+    Defaulted,
+    Current(&'a Lacpdu),
+}
+
+impl RxEntry<'_> {
+    fn state(&self) -> RxState {
+        match self {
+            RxEntry::Initialize => RxState::Initialize,
+            RxEntry::PortDisabled => RxState::PortDisabled,
+            RxEntry::Expired => RxState::Expired,
+            RxEntry::LacpDisabled => RxState::LacpDisabled,
+            RxEntry::Defaulted => RxState::Defaulted,
+            RxEntry::Current(_) => RxState::Current,
+        }
+    }
+}
+
+impl LacpPort {
+    pub(super) fn run_receive(&mut self, now: Instant) {
+        if !self.port_enabled && !self.port_moved && self.rx_state != RxState::Initialize {
+            self.enter_rx(RxEntry::PortDisabled, now);
+        }
+
+        while let Some(next) = self.next_rx_state() {
+            self.enter_rx(next, now);
+        }
+
+        while let Some(pdu) = self.rx_queue.pop_front() {
+            if matches!(
+                self.rx_state,
+                RxState::Expired | RxState::Defaulted | RxState::Current
+            ) {
+                self.enter_rx(RxEntry::Current(&pdu), now);
+            }
+        }
+
+        if self.current_while.expired(now) {
+            match self.rx_state {
+                RxState::Current => self.enter_rx(RxEntry::Expired, now),
+                RxState::Expired => self.enter_rx(RxEntry::Defaulted, now),
+                _ => {}
+            }
+        }
+    }
+
+    fn next_rx_state(&self) -> Option<RxEntry<'static>> {
+        match self.rx_state {
+            RxState::Initialize => Some(RxEntry::PortDisabled),
+            RxState::PortDisabled if self.port_moved => Some(RxEntry::Initialize),
+            RxState::PortDisabled if self.port_enabled && self.lacp_enabled => {
+                Some(RxEntry::Expired)
+            }
+            RxState::PortDisabled if self.port_enabled => Some(RxEntry::LacpDisabled),
+            _ => None,
+        }
+    }
+
+    fn enter_rx(&mut self, entry: RxEntry, now: Instant) {
+        self.rx_state = entry.state();
+        match entry {
+            RxEntry::Initialize => {
+                self.selected = Selection::Unselected;
+                self.record_default();
+                self.actor.state.set(LacpState::EXPIRED, false);
+                self.port_moved = false;
+            }
+            RxEntry::PortDisabled => {
+                self.partner.state.set(LacpState::SYNCHRONIZATION, false);
+                self.current_while.stop();
+            }
+            RxEntry::Expired => {
+                self.partner.state.set(LacpState::SYNCHRONIZATION, false);
+                self.partner.state.set(LacpState::TIMEOUT, true);
+                self.current_while.start(now, SHORT_TIMEOUT_TIME);
+                self.actor.state.set(LacpState::EXPIRED, true);
+            }
+            RxEntry::LacpDisabled => {
+                self.selected = Selection::Unselected;
+                self.record_default();
+                self.partner.state.set(LacpState::AGGREGATION, false);
+                self.actor.state.set(LacpState::EXPIRED, false);
+            }
+            RxEntry::Defaulted => {
+                self.update_default_selected();
+                self.record_default();
+                self.actor.state.set(LacpState::EXPIRED, false);
+                self.current_while.stop();
+            }
+            RxEntry::Current(pdu) => {
+                self.update_selected(pdu);
+                self.update_ntt(pdu);
+                self.record_pdu(pdu);
+                let timeout = if self.actor.state.contains(LacpState::TIMEOUT) {
+                    SHORT_TIMEOUT_TIME
+                } else {
+                    LONG_TIMEOUT_TIME
+                };
+                self.current_while.start(now, timeout);
+                self.actor.state.set(LacpState::EXPIRED, false);
+            }
+        }
+    }
+
+    fn record_pdu(&mut self, pdu: &Lacpdu) {
+        let in_sync = pdu.actor.state.contains(LacpState::SYNCHRONIZATION)
+            && (pdu.partner.same_link(&self.actor)
+                || !pdu.actor.state.contains(LacpState::AGGREGATION));
+        self.partner = pdu.actor;
+        self.partner.state.set(LacpState::SYNCHRONIZATION, in_sync);
+        self.actor.state.set(LacpState::DEFAULTED, false);
+    }
+
+    fn record_default(&mut self) {
+        self.partner = self.partner_admin;
+        self.actor.state.set(LacpState::DEFAULTED, true);
+    }
+
+    fn update_selected(&mut self, pdu: &Lacpdu) {
+        if !pdu.actor.same_link(&self.partner) {
+            self.selected = Selection::Unselected;
+        }
+    }
+
+    fn update_default_selected(&mut self) {
+        if !self.partner_admin.same_link(&self.partner) {
+            self.selected = Selection::Unselected;
+        }
+    }
+
+    fn update_ntt(&mut self, pdu: &Lacpdu) {
+        const SIGNIFICANT: u8 = LacpState::ACTIVITY
+            | LacpState::TIMEOUT
+            | LacpState::AGGREGATION
+            | LacpState::SYNCHRONIZATION;
+        if !pdu.partner.same_link(&self.actor)
+            || pdu.partner.state.bits() & SIGNIFICANT != self.actor.state.bits() & SIGNIFICANT
+        {
+            self.ntt = true;
+        }
+    }
+}
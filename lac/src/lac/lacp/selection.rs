@@ -0,0 +1,69 @@
+use super::*;
+use crate::ffi::CHIP_SDK_PHY_PORT_PER_GROUP_MAX;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Selection {
+    Unselected,
+    Selected,
+    Standby,
+}
+
+impl LacpPort {
+    fn selectable(&self) -> bool {
+        self.port_enabled
+            && matches!(
+                self.rx_state,
+                RxState::Expired | RxState::Defaulted | RxState::Current
+            )
+            && (self.selected != Selection::Unselected || self.mux_state == MuxState::Detached)
+    }
+
+    fn partner_aggregator(&self) -> (SystemId, u16) {
+        (self.partner.system, self.partner.key)
+    }
+}
+
+/// Attaches the ports sharing an actor key to a single aggregator. The
+/// aggregator follows the partner of its best ranked selected port, and ports
+/// negotiated with another partner or beyond the group limit stay in standby.
+pub(super) fn run_selection(ports: &mut BTreeMap<PhyPortId, LacpPort>) {
+    let mut keys: Vec<u16> = ports.values().map(|port| port.actor.key).collect();
+    keys.sort_unstable();
+    keys.dedup();
+
+    for key in keys {
+        let mut candidates: Vec<&mut LacpPort> = Vec::new();
+        for port in ports.values_mut().filter(|port| port.actor.key == key) {
+            if port.selectable() {
+                candidates.push(port);
+            } else if !port.port_enabled {
+                port.selected = Selection::Unselected;
+            }
+        }
+        candidates.sort_by_key(|port| (port.actor.port_priority, port.actor.port));
+
+        let anchor = candidates
+            .iter()
+            .find(|port| port.selected == Selection::Selected)
+            .or(candidates.first())
+            .map(|port| (port.partner_aggregator(), port.is_aggregatable()));
+        let Some((aggregator, aggregatable)) = anchor else {
+            continue;
+        };
+
+        let mut attached = 0;
+        for port in candidates {
+            let fits = attached == 0
+                || (aggregatable
+                    && port.is_aggregatable()
+                    && attached < CHIP_SDK_PHY_PORT_PER_GROUP_MAX);
+            if port.partner_aggregator() == aggregator && fits {
+                port.selected = Selection::Selected;
+                attached += 1;
+            } else {
+                port.selected = Selection::Standby;
+            }
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Default, Copy, Clone)]
+pub(super) struct Timer {
+    deadline: Option<Instant>,
+}
+
+impl Timer {
+    pub fn start(&mut self, now: Instant, duration: Duration) {
+        self.deadline = Some(now + duration);
+    }
+
+    pub fn stop(&mut self) {
+        self.deadline = None;
+    }
+
+    pub fn running(&self) -> bool {
+        self.deadline.is_some()
+    }
+
+    pub fn expired(&self, now: Instant) -> bool {
+        self.deadline.is_some_and(|deadline| now >= deadline)
+    }
+}
@@ -0,0 +1,27 @@
+use super::*;
+use std::time::Instant;
+
+impl LacpPort {
+    pub(super) fn run_transmit(&mut self, now: Instant) -> Option<Lacpdu> {
+        if !self.ntt || !self.port_enabled || self.periodic_state == PeriodicState::NoPeriodic {
+            return None;
+        }
+        while self
+            .tx_history
+            .front()
+            .is_some_and(|sent| now.duration_since(*sent) >= FAST_PERIODIC_TIME)
+        {
+            self.tx_history.pop_front();
+        }
+        if self.tx_history.len() >= TX_LIMIT_PER_FAST_PERIOD {
+            return None;
+        }
+        self.tx_history.push_back(now);
+        self.ntt = false;
+        Some(Lacpdu {
+            actor: self.actor,
+            partner: self.partner,
+            collector_max_delay: 0,
+        })
+    }
+}
@@ -0,0 +1,597 @@
+use super::hash::{FlowDistribution, FlowKey, HashPolicy};
+use super::mac::MacAddr;
+use super::topology::{PortSpeed, Topology, TopologyChange};
+use super::transaction::LagTransaction;
+use crate::ffi::*;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt;
+
+pub type GroupId = u32;
+
+/// Priority of ports without one set. Lower values are preferred.
+pub const DEFAULT_PORT_PRIORITY: u16 = 0x8000;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GroupState {
+    Down,
+    Degraded,
+    Up,
+}
+
+/// Role of a member port in its group.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MemberState {
+    /// Carrying traffic.
+    Active,
+    /// Up, but not needed while enough ports with a higher priority are.
+    Standby,
+    Down,
+    /// Kept out of the group because its speed differs from the group's.
+    SpeedMismatch,
+}
+
+/// Operational rule a group currently breaks.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PolicyViolation {
+    MinLinks {
+        required: usize,
+        active: usize,
+    },
+    SpeedMismatch {
+        port: PhyPortId,
+        speed: PortSpeed,
+        expected: PortSpeed,
+    },
+    /// Every member on this chip is down while members on other chips
+    /// are not.
+    ChipDown(ChipId),
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyViolation::MinLinks { required, active } => {
+                write!(f, "{} active links, at least {} required", active, required)
+            }
+            PolicyViolation::SpeedMismatch {
+                port,
+                speed,
+                expected,
+            } => write!(
+                f,
+                "Port {} runs at {}, the group at {}",
+                port, speed, expected
+            ),
+            PolicyViolation::ChipDown(chip_id) => {
+                write!(f, "All members on chip {} are down", chip_id)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ChipMembers {
+    pub chip_id: ChipId,
+    pub members: usize,
+    pub up: usize,
+}
+
+/// How the members of a group spread over chips.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChipRedundancy {
+    pub group: GroupId,
+    /// Chips holding members, by chip id.
+    pub chips: Vec<ChipMembers>,
+}
+
+impl ChipRedundancy {
+    pub fn spans_chips(&self) -> bool {
+        self.chips.len() > 1
+    }
+
+    /// Chips whose members are all down.
+    pub fn failed_chips(&self) -> Vec<ChipId> {
+        self.chips
+            .iter()
+            .filter(|chip| chip.up == 0)
+            .map(|chip| chip.chip_id)
+            .collect()
+    }
+
+    /// Whether the group keeps an up member if any single chip fails.
+    pub fn is_redundant(&self) -> bool {
+        self.chips.iter().filter(|chip| chip.up > 0).count() > 1
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupStatus {
+    pub state: GroupState,
+    /// Speed members must run at: the configured one, or else that of the
+    /// preferred up member. `None` while no speed is configured and no
+    /// member is up.
+    pub speed: Option<PortSpeed>,
+    pub members: Vec<(PhyPortId, MemberState)>,
+    pub violations: Vec<PolicyViolation>,
+}
+
+impl GroupStatus {
+    pub fn member_state(&self, port: &PhyPortId) -> Option<MemberState> {
+        self.members
+            .iter()
+            .find(|(member, _)| member == port)
+            .map(|(_, state)| *state)
+    }
+
+    pub fn members_in(&self, state: MemberState) -> Vec<PhyPortId> {
+        self.members
+            .iter()
+            .filter(|(_, member_state)| *member_state == state)
+            .map(|(port, _)| *port)
+            .collect()
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LagError {
+    GroupExists(GroupId),
+    GroupNotFound(GroupId),
+    PortAlreadyAggregated(PhyPortId, GroupId),
+    PortNotMember(PhyPortId, GroupId),
+    InvalidMinLinks(GroupId, usize),
+}
+
+impl fmt::Display for LagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LagError::GroupExists(id) => write!(f, "Group {} already exists", id),
+            LagError::GroupNotFound(id) => write!(f, "Group {} not found", id),
+            LagError::PortAlreadyAggregated(port, id) => {
+                write!(f, "Port {} already belongs to group {}", port, id)
+            }
+            LagError::PortNotMember(port, id) => {
+                write!(f, "Port {} is not a member of group {}", port, id)
+            }
+            LagError::InvalidMinLinks(id, min_links) => write!(
+                f,
+                "Group {} cannot require {} links, allowed are 1 to {}",
+                id, min_links, CHIP_SDK_PHY_PORT_PER_GROUP_MAX
+            ),
+        }
+    }
+}
+
+impl Error for LagError {}
+
+pub type LagResult<T> = Result<T, LagError>;
+
+#[derive(Debug, Clone)]
+pub struct LagGroup {
+    id: GroupId,
+    members: Vec<PhyPortId>,
+    mac: Option<MacAddr>,
+    hash_policy: HashPolicy,
+    min_links: usize,
+    speed: Option<PortSpeed>,
+}
+
+impl LagGroup {
+    fn new(id: GroupId) -> Self {
+        LagGroup {
+            id,
+            members: Vec::with_capacity(CHIP_SDK_PHY_PORT_PER_GROUP_MAX),
+            mac: None,
+            hash_policy: HashPolicy::default(),
+            min_links: 1,
+            speed: None,
+        }
+    }
+
+    pub fn id(&self) -> GroupId {
+        self.id
+    }
+
+    pub fn members(&self) -> &[PhyPortId] {
+        &self.members
+    }
+
+    pub fn contains(&self, port: &PhyPortId) -> bool {
+        self.members.contains(port)
+    }
+
+    /// System MAC shared by every member port.
+    pub fn mac(&self) -> Option<MacAddr> {
+        self.mac
+    }
+
+    pub fn hash_policy(&self) -> HashPolicy {
+        self.hash_policy
+    }
+
+    pub(crate) fn set_mac(&mut self, mac: Option<MacAddr>) {
+        self.mac = mac;
+    }
+
+    /// Active members needed for the group to be up.
+    pub fn min_links(&self) -> usize {
+        self.min_links
+    }
+
+    /// Configured member speed. Without one the group runs at the speed of
+    /// its preferred member.
+    pub fn speed(&self) -> Option<PortSpeed> {
+        self.speed
+    }
+}
+
+/// Membership update made by `LagManager::apply_topology_changes`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MembershipChange {
+    /// The port disappeared and left its group.
+    Detached(GroupId, PhyPortId),
+    /// The port came back and rejoined the group it left.
+    Restored(GroupId, PhyPortId),
+}
+
+#[derive(Default, Clone)]
+pub struct LagManager {
+    groups: BTreeMap<GroupId, LagGroup>,
+    owners: HashMap<PhyPortId, GroupId>,
+    /// Members of removed ports, by the group they left.
+    detached: BTreeMap<PhyPortId, GroupId>,
+    link_status: HashMap<PhyPortId, LinkStatus>,
+    speeds: HashMap<PhyPortId, PortSpeed>,
+    priorities: HashMap<PhyPortId, u16>,
+}
+
+impl LagManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn create_group(&mut self, id: GroupId) -> LagResult<()> {
+        if self.groups.contains_key(&id) {
+            return Err(LagError::GroupExists(id));
+        }
+        self.groups.insert(id, LagGroup::new(id));
+        Ok(())
+    }
+
+    /// Starts a batch of changes that is only kept if all of them succeed.
+    pub fn transaction(&mut self) -> LagTransaction<'_> {
+        LagTransaction::new(self)
+    }
+
+    pub fn delete_group(&mut self, id: GroupId) -> LagResult<LagGroup> {
+        let group = self.groups.remove(&id).ok_or(LagError::GroupNotFound(id))?;
+        for port in &group.members {
+            self.owners.remove(port);
+        }
+        self.detached.retain(|_, owner| *owner != id);
+        Ok(group)
+    }
+
+    pub fn group(&self, id: GroupId) -> Option<&LagGroup> {
+        self.groups.get(&id)
+    }
+
+    pub fn group_mut(&mut self, id: GroupId) -> Option<&mut LagGroup> {
+        self.groups.get_mut(&id)
+    }
+
+    pub fn groups(&self) -> impl Iterator<Item = &LagGroup> {
+        self.groups.values()
+    }
+
+    pub fn group_of(&self, port: &PhyPortId) -> Option<GroupId> {
+        self.owners.get(port).copied()
+    }
+
+    pub fn set_group_mac(&mut self, id: GroupId, mac: Option<MacAddr>) -> LagResult<()> {
+        let group = self
+            .groups
+            .get_mut(&id)
+            .ok_or(LagError::GroupNotFound(id))?;
+        group.set_mac(mac);
+        Ok(())
+    }
+
+    pub fn set_hash_policy(&mut self, id: GroupId, policy: HashPolicy) -> LagResult<()> {
+        let group = self
+            .groups
+            .get_mut(&id)
+            .ok_or(LagError::GroupNotFound(id))?;
+        group.hash_policy = policy;
+        Ok(())
+    }
+
+    pub fn set_min_links(&mut self, id: GroupId, min_links: usize) -> LagResult<()> {
+        let group = self
+            .groups
+            .get_mut(&id)
+            .ok_or(LagError::GroupNotFound(id))?;
+        if !(1..=CHIP_SDK_PHY_PORT_PER_GROUP_MAX).contains(&min_links) {
+            return Err(LagError::InvalidMinLinks(id, min_links));
+        }
+        group.min_links = min_links;
+        Ok(())
+    }
+
+    pub fn set_group_speed(&mut self, id: GroupId, speed: Option<PortSpeed>) -> LagResult<()> {
+        let group = self
+            .groups
+            .get_mut(&id)
+            .ok_or(LagError::GroupNotFound(id))?;
+        group.speed = speed;
+        Ok(())
+    }
+
+    pub fn add_member(&mut self, id: GroupId, port: PhyPortId) -> LagResult<()> {
+        if let Some(owner) = self.owners.get(&port) {
+            return Err(LagError::PortAlreadyAggregated(port, *owner));
+        }
+        let group = self
+            .groups
+            .get_mut(&id)
+            .ok_or(LagError::GroupNotFound(id))?;
+        group.members.push(port);
+        self.owners.insert(port, id);
+        Ok(())
+    }
+
+    pub fn remove_member(&mut self, id: GroupId, port: PhyPortId) -> LagResult<()> {
+        let group = self
+            .groups
+            .get_mut(&id)
+            .ok_or(LagError::GroupNotFound(id))?;
+        let index = group
+            .members
+            .iter()
+            .position(|member| *member == port)
+            .ok_or(LagError::PortNotMember(port, id))?;
+        group.members.remove(index);
+        self.owners.remove(&port);
+        Ok(())
+    }
+
+    pub fn link_status(&self, port: &PhyPortId) -> LinkStatus {
+        self.link_status.get(port).copied().unwrap_or_default()
+    }
+
+    pub fn update_link_status(&mut self, port: PhyPortId, status: LinkStatus) {
+        self.link_status.insert(port, status);
+    }
+
+    pub fn port_speed(&self, port: &PhyPortId) -> PortSpeed {
+        self.speeds.get(port).copied().unwrap_or_default()
+    }
+
+    pub fn update_port_speed(&mut self, port: PhyPortId, speed: PortSpeed) {
+        self.speeds.insert(port, speed);
+    }
+
+    pub fn port_priority(&self, port: &PhyPortId) -> u16 {
+        self.priorities
+            .get(port)
+            .copied()
+            .unwrap_or(DEFAULT_PORT_PRIORITY)
+    }
+
+    pub fn set_port_priority(&mut self, port: PhyPortId, priority: u16) {
+        self.priorities.insert(port, priority);
+    }
+
+    /// Priorities set through `set_port_priority`.
+    pub fn port_priorities(&self) -> impl Iterator<Item = (&PhyPortId, &u16)> {
+        self.priorities.iter()
+    }
+
+    /// Follows a topology change: members on removed ports leave their group
+    /// and rejoin it when the port comes back, and speed changes are taken
+    /// over. A port that cannot rejoin, because its group is gone or full,
+    /// stays out.
+    pub fn apply_topology_changes(&mut self, changes: &[TopologyChange]) -> Vec<MembershipChange> {
+        let mut membership = Vec::new();
+        for change in changes {
+            match *change {
+                TopologyChange::PortRemoved(port) => {
+                    self.update_link_status(port, LinkStatus::LINK_DOWN);
+                    if let Some(id) = self.group_of(&port) {
+                        if self.remove_member(id, port).is_ok() {
+                            self.detached.insert(port, id);
+                            membership.push(MembershipChange::Detached(id, port));
+                        }
+                    }
+                }
+                TopologyChange::PortAdded(port) => {
+                    if let Some(id) = self.detached.remove(&port) {
+                        if self.add_member(id, port).is_ok() {
+                            membership.push(MembershipChange::Restored(id, port));
+                        }
+                    }
+                }
+                TopologyChange::SpeedChanged { port, to, .. } => self.update_port_speed(port, to),
+                TopologyChange::ChipAdded(_) | TopologyChange::ChipRemoved(_) => {}
+            }
+        }
+        membership
+    }
+
+    /// Members waiting for their removed port to come back.
+    pub fn detached_members(&self) -> impl Iterator<Item = (PhyPortId, GroupId)> + '_ {
+        self.detached.iter().map(|(port, id)| (*port, *id))
+    }
+
+    /// Takes over the link status and speed of every port in `topology`.
+    pub fn sync_topology(&mut self, topology: &Topology) {
+        for port in topology.ports() {
+            self.update_link_status(port.id, port.status);
+            self.update_port_speed(port.id, port.speed);
+        }
+    }
+
+    /// Members ordered by preference: priority, then port id.
+    fn by_priority(&self, group: &LagGroup) -> Vec<PhyPortId> {
+        let mut members = group.members.clone();
+        members.sort_by_key(|port| (self.port_priority(port), *port));
+        members
+    }
+
+    /// Applies the group policies: members must run at the group speed, at
+    /// most `CHIP_SDK_PHY_PORT_PER_GROUP_MAX` of the preferred up members
+    /// are active and the rest wait in standby, and the group is down while
+    /// fewer than `min_links` are active.
+    pub fn group_status(&self, id: GroupId) -> LagResult<GroupStatus> {
+        let group = self.groups.get(&id).ok_or(LagError::GroupNotFound(id))?;
+        let ordered = self.by_priority(group);
+        // Without a configured speed, the preferred up member sets it.
+        let speed = group.speed.or_else(|| {
+            ordered
+                .iter()
+                .find(|port| self.link_status(port) == LinkStatus::LINK_UP)
+                .map(|port| self.port_speed(port))
+        });
+
+        let mut violations = Vec::new();
+        let mut states = HashMap::new();
+        let mut active = 0;
+        for port in &ordered {
+            let port_speed = self.port_speed(port);
+            let state = if let Some(expected) = speed.filter(|speed| *speed != port_speed) {
+                violations.push(PolicyViolation::SpeedMismatch {
+                    port: *port,
+                    speed: port_speed,
+                    expected,
+                });
+                MemberState::SpeedMismatch
+            } else if self.link_status(port) != LinkStatus::LINK_UP {
+                MemberState::Down
+            } else if active < CHIP_SDK_PHY_PORT_PER_GROUP_MAX {
+                active += 1;
+                MemberState::Active
+            } else {
+                MemberState::Standby
+            };
+            states.insert(*port, state);
+        }
+
+        let redundancy = self.redundancy_of(group);
+        if redundancy.chips.iter().any(|chip| chip.up > 0) {
+            violations.extend(
+                redundancy
+                    .failed_chips()
+                    .into_iter()
+                    .map(PolicyViolation::ChipDown),
+            );
+        }
+
+        let state = if active < group.min_links {
+            if !group.members.is_empty() {
+                violations.push(PolicyViolation::MinLinks {
+                    required: group.min_links,
+                    active,
+                });
+            }
+            GroupState::Down
+        } else if states
+            .values()
+            .all(|state| matches!(state, MemberState::Active | MemberState::Standby))
+        {
+            GroupState::Up
+        } else {
+            GroupState::Degraded
+        };
+        Ok(GroupStatus {
+            state,
+            speed,
+            members: group
+                .members
+                .iter()
+                .map(|port| (*port, states[port]))
+                .collect(),
+            violations,
+        })
+    }
+
+    fn redundancy_of(&self, group: &LagGroup) -> ChipRedundancy {
+        let mut chips: BTreeMap<ChipId, ChipMembers> = BTreeMap::new();
+        for port in &group.members {
+            let chip = chips.entry(port.0).or_insert(ChipMembers {
+                chip_id: port.0,
+                members: 0,
+                up: 0,
+            });
+            chip.members += 1;
+            if self.link_status(port) == LinkStatus::LINK_UP {
+                chip.up += 1;
+            }
+        }
+        ChipRedundancy {
+            group: group.id,
+            chips: chips.into_values().collect(),
+        }
+    }
+
+    pub fn chip_redundancy(&self, id: GroupId) -> LagResult<ChipRedundancy> {
+        let group = self.groups.get(&id).ok_or(LagError::GroupNotFound(id))?;
+        Ok(self.redundancy_of(group))
+    }
+
+    /// Marks every member on `chip_id` down, returning the groups with
+    /// members on it.
+    pub fn fail_chip(&mut self, chip_id: ChipId) -> Vec<GroupId> {
+        let ports: Vec<(PhyPortId, GroupId)> = self
+            .owners
+            .iter()
+            .filter(|(port, _)| port.0 == chip_id)
+            .map(|(port, id)| (*port, *id))
+            .collect();
+        let mut groups: Vec<GroupId> = Vec::new();
+        for (port, id) in ports {
+            self.update_link_status(port, LinkStatus::LINK_DOWN);
+            groups.push(id);
+        }
+        groups.sort();
+        groups.dedup();
+        groups
+    }
+
+    pub fn group_state(&self, id: GroupId) -> LagResult<GroupState> {
+        Ok(self.group_status(id)?.state)
+    }
+
+    /// Members carrying traffic, in the order they were added. Empty while
+    /// the group is down.
+    pub fn active_members(&self, id: GroupId) -> LagResult<Vec<PhyPortId>> {
+        let status = self.group_status(id)?;
+        if status.state == GroupState::Down {
+            return Ok(Vec::new());
+        }
+        Ok(status.members_in(MemberState::Active))
+    }
+
+    /// Egress member of `flow`, or `None` if no member is up.
+    pub fn select_member(&self, id: GroupId, flow: &FlowKey) -> LagResult<Option<PhyPortId>> {
+        let policy = self
+            .group(id)
+            .ok_or(LagError::GroupNotFound(id))?
+            .hash_policy;
+        Ok(policy.select(flow, &self.active_members(id)?))
+    }
+
+    /// How `flows` spread over the members of a group in its current state.
+    pub fn flow_distribution<'a>(
+        &self,
+        id: GroupId,
+        flows: impl IntoIterator<Item = &'a FlowKey>,
+    ) -> LagResult<FlowDistribution> {
+        let group = self.groups.get(&id).ok_or(LagError::GroupNotFound(id))?;
+        let active = self.active_members(id)?;
+        let mut distribution = FlowDistribution::new(&group.members, active.len());
+        for flow in flows {
+            distribution.record(group.hash_policy.select(flow, &active));
+        }
+        Ok(distribution)
+    }
+}
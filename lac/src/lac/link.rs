@@ -0,0 +1,165 @@
+use super::clock::{Clock, SystemClock};
+use super::dampening::LinkDamper;
+use crate::ffi::*;
+use crate::sdk::LinkStatusSink;
+use std::sync::{Arc, Mutex, Weak};
+
+pub type LinkStatusHandler = Box<dyn Fn(PhyPortId, LinkStatus) + Send + Sync>;
+
+#[derive(Default)]
+struct Handlers {
+    next_id: u64,
+    handlers: Vec<(u64, Arc<LinkStatusHandler>)>,
+}
+
+pub(crate) struct Damping {
+    pub(crate) damper: LinkDamper,
+    pub(crate) clock: Arc<dyn Clock + Send + Sync>,
+}
+
+impl Default for Damping {
+    fn default() -> Self {
+        Damping {
+            damper: LinkDamper::new(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
+
+/// Link status subscribers of one device. Reports go through a
+/// `LinkDamper` before reaching them.
+#[derive(Default)]
+pub(crate) struct Subscribers {
+    inner: Mutex<Handlers>,
+    pub(crate) damping: Mutex<Damping>,
+    /// Sees every change before the handlers.
+    observer: Option<LinkStatusHandler>,
+}
+
+impl Subscribers {
+    pub(crate) fn with_observer(observer: LinkStatusHandler) -> Self {
+        Subscribers {
+            observer: Some(observer),
+            ..Default::default()
+        }
+    }
+
+    /// Sink to register with the SDK, dispatching reports to the observer
+    /// and all subscribers.
+    pub(crate) fn sink(self: &Arc<Self>) -> LinkStatusSink {
+        let subscribers = Arc::downgrade(self);
+        Arc::new(move |port, status| {
+            if let Some(subscribers) = subscribers.upgrade() {
+                subscribers.dispatch(port, status);
+            }
+        })
+    }
+
+    pub(crate) fn subscribe(self: &Arc<Self>, handler: LinkStatusHandler) -> LinkSubscription {
+        let mut inner = self.inner.lock().unwrap();
+        let id = inner.next_id;
+        inner.next_id += 1;
+        inner.handlers.push((id, Arc::new(handler)));
+        LinkSubscription {
+            id,
+            subscribers: Arc::downgrade(self),
+        }
+    }
+
+    fn dispatch(&self, port: PhyPortId, status: LinkStatus) {
+        let changes = {
+            let mut damping = self.damping.lock().unwrap();
+            let now = damping.clock.now();
+            let mut changes = damping.damper.expire(now);
+            changes.extend(
+                damping
+                    .damper
+                    .report(port, status, now)
+                    .map(|status| (port, status)),
+            );
+            changes
+        };
+        self.deliver(&changes);
+    }
+
+    /// Drops every handler.
+    pub(crate) fn reset(&self) {
+        self.inner.lock().unwrap().handlers.clear();
+    }
+
+    /// Delivers the changes whose hold timer ran out, returning how many.
+    pub(crate) fn run_timers(&self) -> usize {
+        let changes = {
+            let mut damping = self.damping.lock().unwrap();
+            let now = damping.clock.now();
+            damping.damper.expire(now)
+        };
+        self.deliver(&changes);
+        changes.len()
+    }
+
+    fn deliver(&self, changes: &[(PhyPortId, LinkStatus)]) {
+        if changes.is_empty() {
+            return;
+        }
+        let handlers: Vec<_> = self
+            .inner
+            .lock()
+            .unwrap()
+            .handlers
+            .iter()
+            .map(|(_, handler)| Arc::clone(handler))
+            .collect();
+        for (port, status) in changes {
+            if let Some(observer) = &self.observer {
+                observer(*port, *status);
+            }
+            for handler in &handlers {
+                handler(*port, *status);
+            }
+        }
+    }
+}
+
+/// Handle of a link status subscription, used to stop receiving events.
+#[derive(Debug)]
+pub struct LinkSubscription {
+    id: u64,
+    subscribers: Weak<Subscribers>,
+}
+
+impl LinkSubscription {
+    pub fn unsubscribe(self) {
+        if let Some(subscribers) = self.subscribers.upgrade() {
+            subscribers
+                .inner
+                .lock()
+                .unwrap()
+                .handlers
+                .retain(|(id, _)| *id != self.id);
+        }
+    }
+}
+
+/// Handle running the link hold timers of a device without borrowing it,
+/// for handlers that lock the device themselves.
+#[derive(Clone)]
+pub struct LinkTimers {
+    subscribers: Weak<Subscribers>,
+}
+
+impl LinkTimers {
+    pub(crate) fn new(subscribers: &Arc<Subscribers>) -> Self {
+        LinkTimers {
+            subscribers: Arc::downgrade(subscribers),
+        }
+    }
+
+    /// Same as `Device::run_link_timers`; does nothing once the device is
+    /// dropped.
+    pub fn run(&self) -> usize {
+        self.subscribers
+            .upgrade()
+            .map_or(0, |subscribers| subscribers.run_timers())
+    }
+}
@@ -0,0 +1,151 @@
+use crate::ffi::Mac;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MacAddr([u8; 6]);
+
+impl MacAddr {
+    pub const BROADCAST: MacAddr = MacAddr([0xff; 6]);
+    pub const ZERO: MacAddr = MacAddr([0; 6]);
+
+    const MAX: u64 = 0xffff_ffff_ffff;
+
+    pub const fn new(octets: [u8; 6]) -> Self {
+        MacAddr(octets)
+    }
+
+    pub const fn octets(&self) -> [u8; 6] {
+        self.0
+    }
+
+    pub fn is_multicast(&self) -> bool {
+        self.0[0] & 0x01 != 0
+    }
+
+    pub fn is_unicast(&self) -> bool {
+        !self.is_multicast()
+    }
+
+    pub fn is_broadcast(&self) -> bool {
+        *self == Self::BROADCAST
+    }
+
+    pub fn is_locally_administered(&self) -> bool {
+        self.0[0] & 0x02 != 0
+    }
+
+    pub fn is_universal(&self) -> bool {
+        !self.is_locally_administered()
+    }
+
+    pub fn to_u64(&self) -> u64 {
+        self.0
+            .iter()
+            .fold(0, |value, octet| (value << 8) | *octet as u64)
+    }
+
+    pub fn from_u64(value: u64) -> Option<Self> {
+        if value > Self::MAX {
+            return None;
+        }
+        let bytes = value.to_be_bytes();
+        Some(MacAddr(bytes[2..].try_into().unwrap()))
+    }
+
+    /// Returns the address `offset` positions after this one, or `None` if it
+    /// would run past `ff:ff:ff:ff:ff:ff`.
+    pub fn checked_add(&self, offset: u64) -> Option<Self> {
+        Self::from_u64(self.to_u64().checked_add(offset)?)
+    }
+
+    pub fn checked_sub(&self, offset: u64) -> Option<Self> {
+        Self::from_u64(self.to_u64().checked_sub(offset)?)
+    }
+
+    /// Number of addresses from `base` up to this one.
+    pub fn offset_from(&self, base: &MacAddr) -> Option<u64> {
+        self.to_u64().checked_sub(base.to_u64())
+    }
+}
+
+impl From<[u8; 6]> for MacAddr {
+    fn from(octets: [u8; 6]) -> Self {
+        MacAddr(octets)
+    }
+}
+
+impl From<MacAddr> for [u8; 6] {
+    fn from(mac: MacAddr) -> Self {
+        mac.0
+    }
+}
+
+impl From<Mac> for MacAddr {
+    fn from(mac: Mac) -> Self {
+        MacAddr(mac.addr)
+    }
+}
+
+impl From<MacAddr> for Mac {
+    fn from(mac: MacAddr) -> Self {
+        Mac { addr: mac.0 }
+    }
+}
+
+impl fmt::Display for MacAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [a, b, c, d, e, g] = self.0;
+        write!(
+            f,
+            "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}",
+            a, b, c, d, e, g
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MacParseError(String);
+
+impl fmt::Display for MacParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid MAC address '{}'", self.0)
+    }
+}
+
+impl Error for MacParseError {}
+
+impl FromStr for MacAddr {
+    type Err = MacParseError;
+
+    /// Parses `aa:bb:cc:dd:ee:ff`, `aa-bb-cc-dd-ee-ff` and `aabb.ccdd.eeff`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || MacParseError(s.to_string());
+        let (separator, group_len) = if s.contains('.') {
+            ('.', 4)
+        } else if s.contains('-') {
+            ('-', 2)
+        } else {
+            (':', 2)
+        };
+
+        let groups: Vec<&str> = s.split(separator).collect();
+        if groups.len() != 12 / group_len {
+            return Err(error());
+        }
+        let mut octets = [0u8; 6];
+        let mut index = 0;
+        for group in groups {
+            if group.len() != group_len || !group.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(error());
+            }
+            for pair in group.as_bytes().chunks(2) {
+                let pair = std::str::from_utf8(pair).map_err(|_| error())?;
+                octets[index] = u8::from_str_radix(pair, 16).map_err(|_| error())?;
+                index += 1;
+            }
+        }
+        Ok(MacAddr(octets))
+    }
+}
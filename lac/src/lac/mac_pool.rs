@@ -0,0 +1,344 @@
+use super::device::Device;
+use super::lag::{GroupId, LagError, LagGroup, LagManager};
+use super::mac::MacAddr;
+use super::transaction::{LagOp, TransactionError};
+use crate::ffi::*;
+use crate::sdk::ChipSdk;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MacOwner {
+    Port(PhyPortId),
+    Group(GroupId),
+}
+
+impl fmt::Display for MacOwner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MacOwner::Port(port) => write!(f, "port {}", port),
+            MacOwner::Group(id) => write!(f, "group {}", id),
+        }
+    }
+}
+
+/// A pool address programmed on a port that does not belong to its owner.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MacCollision {
+    pub mac: MacAddr,
+    pub owner: MacOwner,
+    pub port: PhyPortId,
+}
+
+#[derive(Debug)]
+pub enum MacPoolError {
+    InvalidRange(MacAddr, u64),
+    Exhausted,
+    OutOfRange(MacAddr),
+    InUse(MacAddr, MacOwner),
+    /// Programming the members failed; the earlier ones were rolled back.
+    Transaction(TransactionError),
+    Lag(LagError),
+    Parse(String),
+    Serialize(String),
+    Io(io::Error),
+}
+
+impl fmt::Display for MacPoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MacPoolError::InvalidRange(base, size) => {
+                write!(f, "Invalid MAC range of {} addresses from {}", size, base)
+            }
+            MacPoolError::Exhausted => write!(f, "MAC pool exhausted"),
+            MacPoolError::OutOfRange(mac) => write!(f, "MAC {} is outside the pool", mac),
+            MacPoolError::InUse(mac, owner) => write!(f, "MAC {} is assigned to {}", mac, owner),
+            MacPoolError::Transaction(err) => write!(f, "{}", err),
+            MacPoolError::Lag(err) => write!(f, "{}", err),
+            MacPoolError::Parse(err) => write!(f, "Invalid MAC pool file: {}", err),
+            MacPoolError::Serialize(err) => write!(f, "Failed to serialize MAC pool: {}", err),
+            MacPoolError::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for MacPoolError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MacPoolError::Transaction(err) => Some(err),
+            MacPoolError::Lag(err) => Some(err),
+            MacPoolError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<LagError> for MacPoolError {
+    fn from(err: LagError) -> Self {
+        MacPoolError::Lag(err)
+    }
+}
+
+impl From<io::Error> for MacPoolError {
+    fn from(err: io::Error) -> Self {
+        MacPoolError::Io(err)
+    }
+}
+
+pub type MacPoolResult<T> = Result<T, MacPoolError>;
+
+/// Hands out unique unicast addresses for ports and groups from
+/// `size` consecutive addresses starting at `base`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MacPool {
+    base: MacAddr,
+    size: u64,
+    assigned: BTreeMap<MacOwner, MacAddr>,
+    used: BTreeSet<MacAddr>,
+}
+
+impl MacPool {
+    /// Fails unless every address of the range is unicast. Leaving the first
+    /// octet of `base` would pass through an odd, multicast one.
+    pub fn new(base: MacAddr, size: u64) -> MacPoolResult<Self> {
+        let last = size
+            .checked_sub(1)
+            .and_then(|offset| base.checked_add(offset));
+        let unicast =
+            last.is_some_and(|last| base.is_unicast() && last.octets()[0] == base.octets()[0]);
+        if !unicast {
+            return Err(MacPoolError::InvalidRange(base, size));
+        }
+        Ok(MacPool {
+            base,
+            size,
+            assigned: BTreeMap::new(),
+            used: BTreeSet::new(),
+        })
+    }
+
+    pub fn base(&self) -> MacAddr {
+        self.base
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn available(&self) -> u64 {
+        self.size - self.used.len() as u64
+    }
+
+    pub fn contains(&self, mac: &MacAddr) -> bool {
+        mac.offset_from(&self.base)
+            .is_some_and(|offset| offset < self.size)
+    }
+
+    pub fn get(&self, owner: &MacOwner) -> Option<MacAddr> {
+        self.assigned.get(owner).copied()
+    }
+
+    pub fn assignments(&self) -> impl Iterator<Item = (&MacOwner, &MacAddr)> {
+        self.assigned.iter()
+    }
+
+    /// Returns the address of `owner`, assigning the lowest free one that is
+    /// not already programmed on any port if it has none yet.
+    pub fn allocate(
+        &mut self,
+        owner: MacOwner,
+        programmed: &HashMap<PhyPortId, MacAddr>,
+    ) -> MacPoolResult<MacAddr> {
+        if let Some(mac) = self.get(&owner) {
+            return Ok(mac);
+        }
+        let in_use: BTreeSet<&MacAddr> = programmed.values().collect();
+        let mac = (0..self.size)
+            .filter_map(|offset| self.base.checked_add(offset))
+            .find(|mac| !self.used.contains(mac) && !in_use.contains(mac))
+            .ok_or(MacPoolError::Exhausted)?;
+        self.assign(owner, mac)?;
+        Ok(mac)
+    }
+
+    /// Records a known assignment, e.g. one restored from a previous run.
+    pub fn assign(&mut self, owner: MacOwner, mac: MacAddr) -> MacPoolResult<()> {
+        if !self.contains(&mac) {
+            return Err(MacPoolError::OutOfRange(mac));
+        }
+        if let Some((other, _)) = self
+            .assigned
+            .iter()
+            .find(|(other, assigned)| **assigned == mac && **other != owner)
+        {
+            return Err(MacPoolError::InUse(mac, *other));
+        }
+        self.release(&owner);
+        self.assigned.insert(owner, mac);
+        self.used.insert(mac);
+        Ok(())
+    }
+
+    pub fn release(&mut self, owner: &MacOwner) -> Option<MacAddr> {
+        let mac = self.assigned.remove(owner)?;
+        self.used.remove(&mac);
+        Some(mac)
+    }
+
+    /// Finds ports programmed with a pool address assigned to another owner.
+    /// A group address is expected on the ports `group_of` maps to the group.
+    pub fn collisions(
+        &self,
+        programmed: &HashMap<PhyPortId, MacAddr>,
+        group_of: impl Fn(&PhyPortId) -> Option<GroupId>,
+    ) -> Vec<MacCollision> {
+        let owners: HashMap<&MacAddr, &MacOwner> = self
+            .assigned
+            .iter()
+            .map(|(owner, mac)| (mac, owner))
+            .collect();
+        let mut collisions: Vec<MacCollision> = programmed
+            .iter()
+            .filter_map(|(port, mac)| {
+                let owner = **owners.get(mac)?;
+                let expected = match owner {
+                    MacOwner::Port(owner) => owner == *port,
+                    MacOwner::Group(id) => group_of(port) == Some(id),
+                };
+                (!expected).then_some(MacCollision {
+                    mac: *mac,
+                    owner,
+                    port: *port,
+                })
+            })
+            .collect();
+        collisions.sort_by_key(|collision| collision.port);
+        collisions
+    }
+
+    pub fn from_toml(text: &str) -> MacPoolResult<Self> {
+        let file: PoolFile =
+            toml::from_str(text).map_err(|err| MacPoolError::Parse(err.to_string()))?;
+        let mut pool = MacPool::new(file.base, file.size)?;
+        let ports = file
+            .ports
+            .into_iter()
+            .map(|entry| (MacOwner::Port(entry.port), entry.mac));
+        let groups = file
+            .groups
+            .into_iter()
+            .map(|entry| (MacOwner::Group(entry.id), entry.mac));
+        for (owner, mac) in ports.chain(groups) {
+            pool.assign(owner, mac)?;
+        }
+        Ok(pool)
+    }
+
+    pub fn to_toml(&self) -> MacPoolResult<String> {
+        let mut file = PoolFile {
+            base: self.base,
+            size: self.size,
+            ports: Vec::new(),
+            groups: Vec::new(),
+        };
+        for (owner, mac) in &self.assigned {
+            match *owner {
+                MacOwner::Port(port) => file.ports.push(PortEntry { port, mac: *mac }),
+                MacOwner::Group(id) => file.groups.push(GroupEntry { id, mac: *mac }),
+            }
+        }
+        toml::to_string(&file).map_err(|err| MacPoolError::Serialize(err.to_string()))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> MacPoolResult<Self> {
+        Self::from_toml(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> MacPoolResult<()> {
+        fs::write(path, self.to_toml()?)?;
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PortEntry {
+    port: PhyPortId,
+    mac: MacAddr,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GroupEntry {
+    id: GroupId,
+    mac: MacAddr,
+}
+
+/// A `MacPool` stored as TOML, like `LacConfig`.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PoolFile {
+    base: MacAddr,
+    size: u64,
+    #[serde(default, rename = "port", skip_serializing_if = "Vec::is_empty")]
+    ports: Vec<PortEntry>,
+    #[serde(default, rename = "group", skip_serializing_if = "Vec::is_empty")]
+    groups: Vec<GroupEntry>,
+}
+
+/// Creates group `id` with `members` and programs its system MAC from
+/// `pool` on them. The group is not kept if the MAC cannot be applied.
+pub fn create_group_with_mac<S: ChipSdk>(
+    device: &Device<S>,
+    pool: &mut MacPool,
+    lag: &mut LagManager,
+    id: GroupId,
+    members: &[PhyPortId],
+) -> MacPoolResult<MacAddr> {
+    let mut transaction = lag.transaction();
+    transaction.stage(LagOp::CreateGroup(id));
+    for port in members {
+        transaction.stage(LagOp::AddMember(id, *port));
+    }
+    transaction
+        .commit()
+        .map_err(|err| MacPoolError::Lag(err.error))?;
+    let group = lag
+        .group_mut(id)
+        .ok_or(MacPoolError::Lag(LagError::GroupNotFound(id)))?;
+    let result = apply_group_mac(device, pool, group);
+    if result.is_err() {
+        let _ = lag.delete_group(id);
+    }
+    result
+}
+
+/// Allocates the system MAC of a group and programs it on every member port.
+/// If a port fails, the ones before it get their previous MAC back and a
+/// newly allocated address is released.
+pub fn apply_group_mac<S: ChipSdk>(
+    device: &Device<S>,
+    pool: &mut MacPool,
+    group: &mut LagGroup,
+) -> MacPoolResult<MacAddr> {
+    let owner = MacOwner::Group(group.id());
+    let assigned = pool.get(&owner);
+    let mac = pool.allocate(owner, &device.programmed_macs())?;
+    let mut transaction = device.transaction();
+    for port in group.members() {
+        transaction.set_mac(*port, mac);
+    }
+    if let Err(err) = transaction.commit() {
+        if assigned.is_none() {
+            pool.release(&owner);
+        }
+        return Err(MacPoolError::Transaction(err));
+    }
+    group.set_mac(Some(mac));
+    Ok(mac)
+}
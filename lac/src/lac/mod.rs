@@ -1,3 +1,48 @@
+mod clock;
+mod config;
+mod dampening;
 mod device;
+mod events;
+mod hash;
+pub mod lacp;
+mod lag;
+mod link;
+mod mac;
+mod mac_pool;
+mod observer;
+mod retry;
+mod telemetry;
+mod topology;
+mod transaction;
 
-pub use device::Device;
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use config::{
+    ConfigChange, ConfigDiff, ConfigError, ConfigResult, GroupConfig, LacConfig, PortConfig,
+};
+pub use dampening::{
+    DampeningError, DampeningPolicy, DampeningResult, DampingStatus, HoldTimers, LinkDamper,
+};
+pub use device::{Device, DeviceError, DeviceResult, DeviceState};
+pub use events::{LinkEvent, LinkEvents};
+pub use hash::{FlowDistribution, FlowKey, HashPolicy};
+pub use lag::{
+    ChipMembers, ChipRedundancy, GroupId, GroupState, GroupStatus, LagError, LagGroup, LagManager,
+    LagResult, MemberState, MembershipChange, PolicyViolation, DEFAULT_PORT_PRIORITY,
+};
+pub use link::{LinkStatusHandler, LinkSubscription, LinkTimers};
+pub use mac::{MacAddr, MacParseError};
+pub use mac_pool::{
+    apply_group_mac, create_group_with_mac, MacCollision, MacOwner, MacPool, MacPoolError,
+    MacPoolResult,
+};
+pub use observer::{LagEvent, LagEventHandler, LagObserver};
+pub use retry::{Attempt, RetryPolicy};
+pub use telemetry::{GroupTelemetry, LinkTelemetry, PortTelemetry, TelemetrySnapshot};
+pub use topology::{
+    Chip, Port, PortSpeed, PortSpeedParseError, Topology, TopologyChange, TopologyError,
+    TopologyResult,
+};
+pub use transaction::{
+    DeviceOp, LagOp, LagTransaction, LagTransactionError, LagTransactionResult, Rollback,
+    Transaction, TransactionError, TransactionResult,
+};
@@ -0,0 +1,246 @@
+use super::lag::{GroupId, GroupState, LagManager, MemberState};
+use super::mac::MacAddr;
+use crate::ffi::*;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// Group and member state change derived from link status and LAG policy.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LagEvent {
+    /// Enough members are active for the group to carry traffic.
+    GroupUp {
+        group: GroupId,
+        /// Port whose link change brought the group up. `None` when no link
+        /// changed: a config apply or a rescan changed the membership or
+        /// policy instead, and may have moved several ports at once.
+        port: Option<PhyPortId>,
+    },
+    GroupDown {
+        group: GroupId,
+        /// Port whose link change took the group down, `None` as for
+        /// `GroupUp`.
+        port: Option<PhyPortId>,
+    },
+    /// The member was chosen to carry traffic, but does not yet because the
+    /// group is down.
+    MemberSelected { group: GroupId, port: PhyPortId },
+    /// The member is up but not needed.
+    MemberStandby { group: GroupId, port: PhyPortId },
+    /// The member carries traffic.
+    MemberDistributing { group: GroupId, port: PhyPortId },
+    /// The member went down, runs at the wrong speed or left the group.
+    MemberDown { group: GroupId, port: PhyPortId },
+    /// The group MAC was programmed on the member.
+    MacProgrammed {
+        group: GroupId,
+        port: PhyPortId,
+        mac: MacAddr,
+    },
+}
+
+impl LagEvent {
+    pub fn group(&self) -> GroupId {
+        match *self {
+            LagEvent::GroupUp { group, .. }
+            | LagEvent::GroupDown { group, .. }
+            | LagEvent::MemberSelected { group, .. }
+            | LagEvent::MemberStandby { group, .. }
+            | LagEvent::MemberDistributing { group, .. }
+            | LagEvent::MemberDown { group, .. }
+            | LagEvent::MacProgrammed { group, .. } => group,
+        }
+    }
+
+    pub fn port(&self) -> Option<PhyPortId> {
+        match *self {
+            LagEvent::GroupUp { port, .. } | LagEvent::GroupDown { port, .. } => port,
+            LagEvent::MemberSelected { port, .. }
+            | LagEvent::MemberStandby { port, .. }
+            | LagEvent::MemberDistributing { port, .. }
+            | LagEvent::MemberDown { port, .. }
+            | LagEvent::MacProgrammed { port, .. } => Some(port),
+        }
+    }
+}
+
+impl fmt::Display for LagEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LagEvent::GroupUp { group, .. } => write!(f, "group {} up", group),
+            LagEvent::GroupDown { group, .. } => write!(f, "group {} down", group),
+            LagEvent::MemberSelected { group, port } => {
+                write!(f, "port {} selected in group {}", port, group)
+            }
+            LagEvent::MemberStandby { group, port } => {
+                write!(f, "port {} standby in group {}", port, group)
+            }
+            LagEvent::MemberDistributing { group, port } => {
+                write!(f, "port {} distributing in group {}", port, group)
+            }
+            LagEvent::MemberDown { group, port } => {
+                write!(f, "port {} down in group {}", port, group)
+            }
+            LagEvent::MacProgrammed { group, port, mac } => {
+                write!(
+                    f,
+                    "MAC {} programmed on port {} of group {}",
+                    mac, port, group
+                )
+            }
+        }
+    }
+}
+
+pub type LagEventHandler = Box<dyn Fn(&LagEvent) + Send + Sync>;
+
+/// What a member does for its group, from least to most involved.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Role {
+    Down,
+    Standby,
+    Selected,
+    Distributing,
+}
+
+#[derive(Debug, Default, Clone)]
+struct GroupRecord {
+    up: bool,
+    roles: BTreeMap<PhyPortId, Role>,
+    macs: BTreeMap<PhyPortId, MacAddr>,
+}
+
+/// Turns successive states of a `LagManager` into `LagEvent`s. Groups seen
+/// for the first time are compared against an empty, down group.
+#[derive(Debug, Default, Clone)]
+pub struct LagObserver {
+    groups: BTreeMap<GroupId, GroupRecord>,
+}
+
+impl LagObserver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn record(lag: &LagManager, id: GroupId, macs: &HashMap<PhyPortId, MacAddr>) -> GroupRecord {
+        let Ok(status) = lag.group_status(id) else {
+            return GroupRecord::default();
+        };
+        let up = status.state != GroupState::Down;
+        let roles = status
+            .members
+            .iter()
+            .map(|(port, state)| {
+                let role = match state {
+                    MemberState::Active if up => Role::Distributing,
+                    MemberState::Active => Role::Selected,
+                    MemberState::Standby => Role::Standby,
+                    MemberState::Down | MemberState::SpeedMismatch => Role::Down,
+                };
+                (*port, role)
+            })
+            .collect();
+        let group_mac = lag.group(id).and_then(|group| group.mac());
+        let macs = status
+            .members
+            .iter()
+            .filter_map(|(port, _)| {
+                macs.get(port)
+                    .filter(|mac| Some(**mac) == group_mac)
+                    .map(|mac| (*port, *mac))
+            })
+            .collect();
+        GroupRecord { up, roles, macs }
+    }
+
+    /// Compares `lag` and the MACs programmed on the device with the last
+    /// observation and returns what changed. Members stepping back are
+    /// reported first, then members stepping up, the group state, and
+    /// finally members starting to distribute. `trigger` is the port whose
+    /// link change is being observed, or `None` when the change came from
+    /// anything else.
+    pub fn observe(
+        &mut self,
+        lag: &LagManager,
+        macs: &HashMap<PhyPortId, MacAddr>,
+        trigger: Option<PhyPortId>,
+    ) -> Vec<LagEvent> {
+        let mut events = Vec::new();
+        let mut ids: Vec<GroupId> = self.groups.keys().copied().collect();
+        ids.extend(lag.groups().map(|group| group.id()));
+        ids.sort();
+        ids.dedup();
+        for id in ids {
+            let new = Self::record(lag, id, macs);
+            let old = self.groups.remove(&id).unwrap_or_default();
+            Self::compare(id, &old, &new, trigger, &mut events);
+            if lag.group(id).is_some() {
+                self.groups.insert(id, new);
+            }
+        }
+        events
+    }
+
+    fn compare(
+        group: GroupId,
+        old: &GroupRecord,
+        new: &GroupRecord,
+        trigger: Option<PhyPortId>,
+        events: &mut Vec<LagEvent>,
+    ) {
+        let mut ports: Vec<PhyPortId> = old.roles.keys().copied().collect();
+        ports.extend(new.roles.keys().copied());
+        ports.sort();
+        ports.dedup();
+        let role = |record: &GroupRecord, port| record.roles.get(port).copied();
+        let changes: Vec<_> = ports
+            .iter()
+            .map(|port| {
+                let from = role(old, port).unwrap_or(Role::Down);
+                let to = role(new, port).unwrap_or(Role::Down);
+                (*port, from, to)
+            })
+            .filter(|(_, from, to)| from != to)
+            .collect();
+
+        for (port, _, to) in changes.iter().filter(|(_, from, to)| to < from) {
+            events.push(match to {
+                Role::Down => LagEvent::MemberDown { group, port: *port },
+                Role::Standby => LagEvent::MemberStandby { group, port: *port },
+                _ => LagEvent::MemberSelected { group, port: *port },
+            });
+        }
+        for (port, from, to) in changes.iter().filter(|(_, from, to)| to > from) {
+            if *to == Role::Standby {
+                events.push(LagEvent::MemberStandby { group, port: *port });
+            } else if *from < Role::Selected {
+                events.push(LagEvent::MemberSelected { group, port: *port });
+            }
+        }
+        match (old.up, new.up) {
+            (false, true) => events.push(LagEvent::GroupUp {
+                group,
+                port: trigger,
+            }),
+            (true, false) => events.push(LagEvent::GroupDown {
+                group,
+                port: trigger,
+            }),
+            _ => {}
+        }
+        for (port, _, _) in changes
+            .iter()
+            .filter(|(_, from, to)| to > from && *to == Role::Distributing)
+        {
+            events.push(LagEvent::MemberDistributing { group, port: *port });
+        }
+        for (port, mac) in &new.macs {
+            if old.macs.get(port) != Some(mac) {
+                events.push(LagEvent::MacProgrammed {
+                    group,
+                    port: *port,
+                    mac: *mac,
+                });
+            }
+        }
+    }
+}
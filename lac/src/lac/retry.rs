@@ -0,0 +1,144 @@
+use crate::ffi::*;
+use crate::sdk::SdkOp;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// One call of an SDK operation made under a `RetryPolicy`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Attempt {
+    pub op: SdkOp,
+    /// Attempt number, counted from 1.
+    pub number: u32,
+    pub result: SdkResult,
+    /// Time from the first attempt to the end of this one.
+    pub elapsed: Duration,
+    /// Delay before the next attempt, if there is one.
+    pub backoff: Option<Duration>,
+}
+
+const GOLDEN_GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+static JITTER_STATE: AtomicU64 = AtomicU64::new(GOLDEN_GAMMA);
+
+/// Next value in [0, 1) of a SplitMix64 sequence shared by all policies.
+/// Jitter only has to spread retries, not be unpredictable.
+fn random() -> f64 {
+    let mut z = JITTER_STATE
+        .fetch_add(GOLDEN_GAMMA, Ordering::Relaxed)
+        .wrapping_add(GOLDEN_GAMMA);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// How transient SDK errors are retried. Permanent errors are returned
+/// immediately.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one.
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub multiplier: u32,
+    /// Fraction of the backoff, between 0 and 1, randomly added or removed.
+    /// The result still stays within `max_backoff`.
+    pub jitter: f64,
+    /// Gives up instead of waiting past this time from the first attempt.
+    pub deadline: Option<Duration>,
+}
+
+impl RetryPolicy {
+    /// Makes a single attempt.
+    pub fn never() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Backoff after the `retry`-th failed attempt, before jitter.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = self.multiplier.saturating_pow(retry.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+
+    fn jittered(&self, backoff: Duration) -> Duration {
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter == 0.0 {
+            return backoff;
+        }
+        // Saturates instead of panicking when the product overflows.
+        Duration::try_from_secs_f64(backoff.as_secs_f64() * (1.0 + jitter * (2.0 * random() - 1.0)))
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff))
+    }
+
+    /// Runs `call` until it succeeds, fails permanently or runs out of
+    /// attempts or time, returning the last result and every attempt made.
+    pub fn run(&self, op: SdkOp, mut call: impl FnMut() -> SdkResult) -> (SdkResult, Vec<Attempt>) {
+        let (result, attempts, ()) = self.run_with(op, || (call(), ()));
+        (result, attempts)
+    }
+
+    /// Like `run`, but `call` also returns a value, such as a lock guard,
+    /// that is dropped before backing off. The value of the last attempt is
+    /// returned.
+    pub fn run_with<T>(
+        &self,
+        op: SdkOp,
+        mut call: impl FnMut() -> (SdkResult, T),
+    ) -> (SdkResult, Vec<Attempt>, T) {
+        let start = Instant::now();
+        let mut attempts = Vec::new();
+        let mut number = 0;
+        loop {
+            number += 1;
+            let (result, value) = call();
+            let retry = match result {
+                Err(err) => err.is_transient() && number < self.max_attempts,
+                Ok(()) => false,
+            };
+            let backoff = retry
+                .then(|| self.jittered(self.backoff(number)))
+                .filter(|backoff| {
+                    self.deadline.is_none_or(|deadline| {
+                        start
+                            .elapsed()
+                            .checked_add(*backoff)
+                            .is_some_and(|end| end <= deadline)
+                    })
+                });
+            attempts.push(Attempt {
+                op,
+                number,
+                result,
+                elapsed: start.elapsed(),
+                backoff,
+            });
+            match backoff {
+                Some(backoff) => {
+                    drop(value);
+                    thread::sleep(backoff);
+                }
+                None => return (result, attempts, value),
+            }
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Five attempts backing off from 10ms up to 200ms, within one second.
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(200),
+            multiplier: 2,
+            jitter: 0.2,
+            deadline: Some(Duration::from_secs(1)),
+        }
+    }
+}
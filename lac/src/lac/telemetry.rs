@@ -0,0 +1,244 @@
+use super::clock::{Clock, SystemClock};
+use super::lag::{GroupId, GroupState, LagManager, MemberState};
+use super::topology::Topology;
+use crate::ffi::*;
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::{Duration, Instant};
+
+/// Link counters of a port at the time of a snapshot.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PortTelemetry {
+    pub port: PhyPortId,
+    pub status: LinkStatus,
+    pub up_transitions: u64,
+    pub down_transitions: u64,
+    /// When the link last changed state, `None` if it never did.
+    pub last_change: Option<Instant>,
+    /// Total time spent up since tracking started.
+    pub uptime: Duration,
+    /// Time spent in the current state.
+    pub state_duration: Duration,
+}
+
+impl PortTelemetry {
+    /// Up and down transitions together.
+    pub fn flaps(&self) -> u64 {
+        self.up_transitions + self.down_transitions
+    }
+}
+
+/// Health counters of a group at the time of a snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupTelemetry {
+    pub group: GroupId,
+    pub state: GroupState,
+    /// Time spent in the current state.
+    pub state_duration: Duration,
+    /// Total time spent degraded since the group was first observed.
+    pub time_degraded: Duration,
+    /// Number of times a port joined or left the active members.
+    pub member_churn: u64,
+    pub active_members: Vec<PhyPortId>,
+}
+
+/// Point-in-time copy of all port and group counters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TelemetrySnapshot {
+    pub taken_at: Instant,
+    pub ports: BTreeMap<PhyPortId, PortTelemetry>,
+    pub groups: BTreeMap<GroupId, GroupTelemetry>,
+}
+
+impl TelemetrySnapshot {
+    pub fn port(&self, port: &PhyPortId) -> Option<&PortTelemetry> {
+        self.ports.get(port)
+    }
+
+    pub fn group(&self, id: GroupId) -> Option<&GroupTelemetry> {
+        self.groups.get(&id)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PortRecord {
+    status: LinkStatus,
+    since: Instant,
+    up_transitions: u64,
+    down_transitions: u64,
+    last_change: Option<Instant>,
+    /// Uptime of the periods that already ended.
+    uptime: Duration,
+}
+
+#[derive(Debug, Clone)]
+struct GroupRecord {
+    state: GroupState,
+    since: Instant,
+    /// Degraded time of the periods that already ended.
+    time_degraded: Duration,
+    member_churn: u64,
+    active: BTreeSet<PhyPortId>,
+}
+
+/// Per-port and per-group link counters, fed from link status callbacks.
+/// Durations are measured with the clock given at construction.
+pub struct LinkTelemetry<C: Clock = SystemClock> {
+    clock: C,
+    ports: BTreeMap<PhyPortId, PortRecord>,
+    groups: BTreeMap<GroupId, GroupRecord>,
+}
+
+impl LinkTelemetry<SystemClock> {
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock)
+    }
+}
+
+impl Default for LinkTelemetry<SystemClock> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Clock> LinkTelemetry<C> {
+    pub fn with_clock(clock: C) -> Self {
+        LinkTelemetry {
+            clock,
+            ports: BTreeMap::new(),
+            groups: BTreeMap::new(),
+        }
+    }
+
+    fn port_record(status: LinkStatus, now: Instant) -> PortRecord {
+        PortRecord {
+            status,
+            since: now,
+            up_transitions: 0,
+            down_transitions: 0,
+            last_change: None,
+            uptime: Duration::ZERO,
+        }
+    }
+
+    /// Starts tracking the ports of `topology` in their current state,
+    /// which does not count as a transition. Ports already tracked keep
+    /// their counters.
+    pub fn sync_topology(&mut self, topology: &Topology) {
+        let now = self.clock.now();
+        for port in topology.ports() {
+            self.ports
+                .entry(port.id)
+                .or_insert_with(|| Self::port_record(port.status, now));
+        }
+    }
+
+    /// Records a link status report. Reports repeating the current state
+    /// are ignored.
+    pub fn record_link(&mut self, port: PhyPortId, status: LinkStatus) {
+        let now = self.clock.now();
+        let record = self
+            .ports
+            .entry(port)
+            .or_insert_with(|| Self::port_record(LinkStatus::LINK_DOWN, now));
+        if record.status == status {
+            return;
+        }
+        if record.status == LinkStatus::LINK_UP {
+            record.uptime += now.saturating_duration_since(record.since);
+        }
+        match status {
+            LinkStatus::LINK_UP => record.up_transitions += 1,
+            LinkStatus::LINK_DOWN => record.down_transitions += 1,
+        }
+        record.status = status;
+        record.since = now;
+        record.last_change = Some(now);
+    }
+
+    /// Updates group counters from the current state of `lag`. Call it after
+    /// every change to link status or membership; groups no longer in `lag`
+    /// are forgotten.
+    pub fn observe_groups(&mut self, lag: &LagManager) {
+        let now = self.clock.now();
+        self.groups
+            .retain(|id, _| lag.groups().any(|group| group.id() == *id));
+        for group in lag.groups() {
+            let Ok(status) = lag.group_status(group.id()) else {
+                continue;
+            };
+            let active: BTreeSet<_> = status.members_in(MemberState::Active).into_iter().collect();
+            let Some(record) = self.groups.get_mut(&group.id()) else {
+                self.groups.insert(
+                    group.id(),
+                    GroupRecord {
+                        state: status.state,
+                        since: now,
+                        time_degraded: Duration::ZERO,
+                        member_churn: 0,
+                        active,
+                    },
+                );
+                continue;
+            };
+            record.member_churn += record.active.symmetric_difference(&active).count() as u64;
+            record.active = active;
+            if record.state != status.state {
+                if record.state == GroupState::Degraded {
+                    record.time_degraded += now.saturating_duration_since(record.since);
+                }
+                record.state = status.state;
+                record.since = now;
+            }
+        }
+    }
+
+    pub fn snapshot(&self) -> TelemetrySnapshot {
+        let now = self.clock.now();
+        let ports = self
+            .ports
+            .iter()
+            .map(|(port, record)| {
+                let state_duration = now.saturating_duration_since(record.since);
+                let mut uptime = record.uptime;
+                if record.status == LinkStatus::LINK_UP {
+                    uptime += state_duration;
+                }
+                let telemetry = PortTelemetry {
+                    port: *port,
+                    status: record.status,
+                    up_transitions: record.up_transitions,
+                    down_transitions: record.down_transitions,
+                    last_change: record.last_change,
+                    uptime,
+                    state_duration,
+                };
+                (*port, telemetry)
+            })
+            .collect();
+        let groups = self
+            .groups
+            .iter()
+            .map(|(id, record)| {
+                let state_duration = now.saturating_duration_since(record.since);
+                let mut time_degraded = record.time_degraded;
+                if record.state == GroupState::Degraded {
+                    time_degraded += state_duration;
+                }
+                let telemetry = GroupTelemetry {
+                    group: *id,
+                    state: record.state,
+                    state_duration,
+                    time_degraded,
+                    member_churn: record.member_churn,
+                    active_members: record.active.iter().copied().collect(),
+                };
+                (*id, telemetry)
+            })
+            .collect();
+        TelemetrySnapshot {
+            taken_at: now,
+            ports,
+            groups,
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use crate::ffi::*;
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Port speed in megabits per second.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PortSpeed(u32);
+
+impl PortSpeed {
+    pub const UNKNOWN: PortSpeed = PortSpeed(0);
+    pub const MBPS_100: PortSpeed = PortSpeed(100);
+    pub const GBPS_1: PortSpeed = PortSpeed(1_000);
+    pub const GBPS_10: PortSpeed = PortSpeed(10_000);
+    pub const GBPS_25: PortSpeed = PortSpeed(25_000);
+    pub const GBPS_40: PortSpeed = PortSpeed(40_000);
+    pub const GBPS_100: PortSpeed = PortSpeed(100_000);
+
+    pub const fn from_mbps(mbps: u32) -> Self {
+        PortSpeed(mbps)
+    }
+
+    pub const fn mbps(&self) -> u32 {
+        self.0
+    }
+}
+
+impl TryFrom<i32> for PortSpeed {
+    type Error = i32;
+
+    fn try_from(mbps: i32) -> Result<Self, Self::Error> {
+        u32::try_from(mbps).map(PortSpeed).map_err(|_| mbps)
+    }
+}
+
+impl fmt::Display for PortSpeed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 == 0 {
+            write!(f, "unknown")
+        } else if self.0.is_multiple_of(1000) {
+            write!(f, "{}G", self.0 / 1000)
+        } else {
+            write!(f, "{}M", self.0)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortSpeedParseError(String);
+
+impl fmt::Display for PortSpeedParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid port speed '{}'", self.0)
+    }
+}
+
+impl Error for PortSpeedParseError {}
+
+impl FromStr for PortSpeed {
+    type Err = PortSpeedParseError;
+
+    /// Parses `25G`, `100M`, `unknown` or plain megabits.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || PortSpeedParseError(s.to_string());
+        if s == "unknown" {
+            return Ok(PortSpeed::UNKNOWN);
+        }
+        let (digits, scale) = match s.strip_suffix('G') {
+            Some(digits) => (digits, 1000),
+            None => (s.strip_suffix('M').unwrap_or(s), 1),
+        };
+        let value: u32 = digits.parse().map_err(|_| error())?;
+        value.checked_mul(scale).map(PortSpeed).ok_or_else(error)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Port {
+    pub id: PhyPortId,
+    pub speed: PortSpeed,
+    pub status: LinkStatus,
+}
+
+impl Port {
+    pub fn is_up(&self) -> bool {
+        self.status == LinkStatus::LINK_UP
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chip {
+    id: ChipId,
+    ports: Vec<Port>,
+}
+
+impl Chip {
+    pub fn id(&self) -> ChipId {
+        self.id
+    }
+
+    pub fn ports(&self) -> impl Iterator<Item = &Port> {
+        self.ports.iter()
+    }
+
+    pub fn port(&self, port_id: i32) -> Option<&Port> {
+        self.ports.iter().find(|port| port.id.1 == port_id)
+    }
+}
+
+/// Difference between two topologies, as reported by `Topology::diff`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TopologyChange {
+    ChipAdded(ChipId),
+    ChipRemoved(ChipId),
+    PortAdded(PhyPortId),
+    PortRemoved(PhyPortId),
+    SpeedChanged {
+        port: PhyPortId,
+        from: PortSpeed,
+        to: PortSpeed,
+    },
+}
+
+impl fmt::Display for TopologyChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TopologyChange::ChipAdded(chip_id) => write!(f, "chip {} added", chip_id),
+            TopologyChange::ChipRemoved(chip_id) => write!(f, "chip {} removed", chip_id),
+            TopologyChange::PortAdded(port) => write!(f, "port {} added", port),
+            TopologyChange::PortRemoved(port) => write!(f, "port {} removed", port),
+            TopologyChange::SpeedChanged { port, from, to } => {
+                write!(f, "port {} speed changed from {} to {}", port, from, to)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TopologyError {
+    TooManyChips(usize),
+    TooManyPorts(ChipId, i32),
+    DuplicateChip(ChipId),
+    DuplicatePort(PhyPortId),
+    InvalidSpeed(PhyPortId, i32),
+}
+
+impl fmt::Display for TopologyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TopologyError::TooManyChips(num) => write!(
+                f,
+                "{} chips reported, at most {} supported",
+                num, CHIP_SDK_CHIP_MAX
+            ),
+            TopologyError::TooManyPorts(chip_id, num) => write!(
+                f,
+                "Chip {} reports {} ports, at most {} supported",
+                chip_id, num, CHIP_SDK_PHY_PORT_PER_CHIP
+            ),
+            TopologyError::DuplicateChip(chip_id) => {
+                write!(f, "Chip {} reported more than once", chip_id)
+            }
+            TopologyError::DuplicatePort(port) => {
+                write!(f, "Port {} reported more than once", port)
+            }
+            TopologyError::InvalidSpeed(port, speed) => {
+                write!(f, "Port {} reports invalid speed {}", port, speed)
+            }
+        }
+    }
+}
+
+impl Error for TopologyError {}
+
+pub type TopologyResult<T> = Result<T, TopologyError>;
+
+/// Validated view of the chips and ports reported by the SDK.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Topology {
+    chips: Vec<Chip>,
+}
+
+impl Topology {
+    pub fn from_raw(chips: &[SwitchChip]) -> TopologyResult<Self> {
+        if chips.len() > CHIP_SDK_CHIP_MAX {
+            return Err(TopologyError::TooManyChips(chips.len()));
+        }
+        let mut chip_ids = BTreeSet::new();
+        let mut topology = Topology::default();
+        for raw in chips {
+            if !chip_ids.insert(raw.chip_id) {
+                return Err(TopologyError::DuplicateChip(raw.chip_id));
+            }
+            let port_num = usize::try_from(raw.numOfPorts)
+                .ok()
+                .filter(|num| *num <= CHIP_SDK_PHY_PORT_PER_CHIP)
+                .ok_or(TopologyError::TooManyPorts(raw.chip_id, raw.numOfPorts))?;
+            let mut chip = Chip {
+                id: raw.chip_id,
+                ports: Vec::with_capacity(port_num),
+            };
+            for raw_port in &raw.ports[..port_num] {
+                let id = PhyPortId(raw.chip_id, raw_port.port_id);
+                if chip.port(raw_port.port_id).is_some() {
+                    return Err(TopologyError::DuplicatePort(id));
+                }
+                let speed = PortSpeed::try_from(raw_port.speed)
+                    .map_err(|speed| TopologyError::InvalidSpeed(id, speed))?;
+                chip.ports.push(Port {
+                    id,
+                    speed,
+                    status: raw_port.status,
+                });
+            }
+            topology.chips.push(chip);
+        }
+        Ok(topology)
+    }
+
+    pub fn chips(&self) -> impl Iterator<Item = &Chip> {
+        self.chips.iter()
+    }
+
+    pub fn ports(&self) -> impl Iterator<Item = &Port> {
+        self.chips.iter().flat_map(|chip| chip.ports.iter())
+    }
+
+    pub fn chip(&self, chip_id: ChipId) -> Option<&Chip> {
+        self.chips.iter().find(|chip| chip.id == chip_id)
+    }
+
+    pub fn port(&self, phy_port_id: &PhyPortId) -> Option<&Port> {
+        self.chip(phy_port_id.0)?.port(phy_port_id.1)
+    }
+
+    pub fn contains(&self, phy_port_id: &PhyPortId) -> bool {
+        self.port(phy_port_id).is_some()
+    }
+
+    /// Changes from `self` to `new`. Ports of added and removed chips are
+    /// reported too, after the chip for additions and before it for
+    /// removals. Link status is not compared.
+    pub fn diff(&self, new: &Topology) -> Vec<TopologyChange> {
+        let mut changes = Vec::new();
+        for chip in &self.chips {
+            let Some(new_chip) = new.chip(chip.id) else {
+                changes.extend(
+                    chip.ports()
+                        .map(|port| TopologyChange::PortRemoved(port.id)),
+                );
+                changes.push(TopologyChange::ChipRemoved(chip.id));
+                continue;
+            };
+            for port in chip.ports() {
+                match new_chip.port(port.id.1) {
+                    None => changes.push(TopologyChange::PortRemoved(port.id)),
+                    Some(new_port) if new_port.speed != port.speed => {
+                        changes.push(TopologyChange::SpeedChanged {
+                            port: port.id,
+                            from: port.speed,
+                            to: new_port.speed,
+                        })
+                    }
+                    Some(_) => {}
+                }
+            }
+            changes.extend(
+                new_chip
+                    .ports()
+                    .filter(|port| chip.port(port.id.1).is_none())
+                    .map(|port| TopologyChange::PortAdded(port.id)),
+            );
+        }
+        for new_chip in new.chips() {
+            if self.chip(new_chip.id).is_none() {
+                changes.push(TopologyChange::ChipAdded(new_chip.id));
+                changes.extend(
+                    new_chip
+                        .ports()
+                        .map(|port| TopologyChange::PortAdded(port.id)),
+                );
+            }
+        }
+        changes
+    }
+
+    /// Records a new link status, returning whether the port exists.
+    pub(crate) fn set_link_status(&mut self, phy_port_id: &PhyPortId, status: LinkStatus) -> bool {
+        let port = self
+            .chips
+            .iter_mut()
+            .filter(|chip| chip.id == phy_port_id.0)
+            .flat_map(|chip| chip.ports.iter_mut())
+            .find(|port| port.id == *phy_port_id);
+        match port {
+            Some(port) => {
+                port.status = status;
+                true
+            }
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,307 @@
+use super::device::{Device, DeviceError, DeviceResult};
+use super::hash::HashPolicy;
+use super::lag::{GroupId, LagError, LagManager, LagResult};
+use super::mac::MacAddr;
+use super::topology::PortSpeed;
+use crate::ffi::*;
+use crate::sdk::ChipSdk;
+use std::error::Error;
+use std::fmt;
+
+/// SDK operation staged in a `Transaction`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DeviceOp {
+    SetMac(PhyPortId, MacAddr),
+}
+
+impl DeviceOp {
+    pub fn port(&self) -> PhyPortId {
+        match *self {
+            DeviceOp::SetMac(port, _) => port,
+        }
+    }
+}
+
+impl fmt::Display for DeviceOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeviceOp::SetMac(port, mac) => write!(f, "set port {} mac {}", port, mac),
+        }
+    }
+}
+
+/// Outcome of undoing the steps a failed transaction had applied.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Rollback {
+    /// Compensating operations that succeeded, in the order they ran.
+    pub undone: Vec<DeviceOp>,
+    /// Compensating operations that failed.
+    pub failed: Vec<(DeviceOp, DeviceError)>,
+    /// Ports that had no MAC before the transaction. The SDK cannot clear a
+    /// MAC, so they keep the one the transaction programmed, which
+    /// `Device::mac` keeps reporting.
+    pub unrestorable: Vec<PhyPortId>,
+}
+
+impl Rollback {
+    /// Whether the device is back in its previous state.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty() && self.unrestorable.is_empty()
+    }
+}
+
+impl fmt::Display for Rollback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_complete() {
+            return write!(f, "rolled back {} steps", self.undone.len());
+        }
+        write!(f, "rollback incomplete")?;
+        for (op, err) in &self.failed {
+            write!(f, ", failed to {}: {}", op, err)?;
+        }
+        for port in &self.unrestorable {
+            write!(f, ", port {} has no MAC to restore", port)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionError {
+    /// Index of the failed step.
+    pub step: usize,
+    pub op: DeviceOp,
+    pub error: DeviceError,
+    pub rollback: Rollback,
+}
+
+impl fmt::Display for TransactionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Step {} failed to {}: {}; {}",
+            self.step + 1,
+            self.op,
+            self.error,
+            self.rollback
+        )
+    }
+}
+
+impl Error for TransactionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+pub type TransactionResult<T> = Result<T, TransactionError>;
+
+/// SDK operations applied in order by `commit`. If one fails, the steps
+/// before it are undone in reverse order. Other users of the device are not
+/// locked out in between.
+///
+/// A MAC can only be undone by programming the previous one, so ports that
+/// had none are left with the new MAC and reported as unrestorable. Staging
+/// a MAC on such ports first makes the transaction fully reversible.
+pub struct Transaction<'a, S: ChipSdk> {
+    device: &'a Device<S>,
+    ops: Vec<DeviceOp>,
+}
+
+impl<'a, S: ChipSdk> Transaction<'a, S> {
+    pub(crate) fn new(device: &'a Device<S>) -> Self {
+        Transaction {
+            device,
+            ops: Vec::new(),
+        }
+    }
+
+    pub fn stage(&mut self, op: DeviceOp) -> &mut Self {
+        self.ops.push(op);
+        self
+    }
+
+    pub fn set_mac(&mut self, phy_port_id: PhyPortId, mac: MacAddr) -> &mut Self {
+        self.stage(DeviceOp::SetMac(phy_port_id, mac))
+    }
+
+    pub fn ops(&self) -> &[DeviceOp] {
+        &self.ops
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    fn run(&self, op: &DeviceOp) -> DeviceResult<()> {
+        match op {
+            DeviceOp::SetMac(port, mac) => self.device.set_mac(port, *mac),
+        }
+    }
+
+    pub fn commit(self) -> TransactionResult<()> {
+        let mut applied = Vec::new();
+        for (step, op) in self.ops.iter().enumerate() {
+            let previous = match op {
+                DeviceOp::SetMac(port, _) => self.device.mac(port),
+            };
+            if let Err(error) = self.run(op) {
+                return Err(TransactionError {
+                    step,
+                    op: *op,
+                    error,
+                    rollback: self.roll_back(applied),
+                });
+            }
+            applied.push((*op, previous));
+        }
+        Ok(())
+    }
+
+    fn roll_back(&self, applied: Vec<(DeviceOp, Option<MacAddr>)>) -> Rollback {
+        let mut rollback = Rollback::default();
+        for (op, previous) in applied.into_iter().rev() {
+            let port = op.port();
+            let Some(previous) = previous else {
+                rollback.unrestorable.push(port);
+                continue;
+            };
+            let undo = DeviceOp::SetMac(port, previous);
+            match self.run(&undo) {
+                Ok(()) => rollback.undone.push(undo),
+                Err(err) => rollback.failed.push((undo, err)),
+            }
+        }
+        rollback
+    }
+}
+
+/// Group manager change staged in a `LagTransaction`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LagOp {
+    CreateGroup(GroupId),
+    DeleteGroup(GroupId),
+    AddMember(GroupId, PhyPortId),
+    RemoveMember(GroupId, PhyPortId),
+    SetHashPolicy(GroupId, HashPolicy),
+    SetMinLinks(GroupId, usize),
+    SetGroupSpeed(GroupId, Option<PortSpeed>),
+    SetGroupMac(GroupId, Option<MacAddr>),
+    SetPortPriority(PhyPortId, u16),
+}
+
+impl LagOp {
+    fn apply(&self, lag: &mut LagManager) -> LagResult<()> {
+        match *self {
+            LagOp::CreateGroup(id) => lag.create_group(id),
+            LagOp::DeleteGroup(id) => lag.delete_group(id).map(|_| ()),
+            LagOp::AddMember(id, port) => lag.add_member(id, port),
+            LagOp::RemoveMember(id, port) => lag.remove_member(id, port),
+            LagOp::SetHashPolicy(id, policy) => lag.set_hash_policy(id, policy),
+            LagOp::SetMinLinks(id, min_links) => lag.set_min_links(id, min_links),
+            LagOp::SetGroupSpeed(id, speed) => lag.set_group_speed(id, speed),
+            LagOp::SetGroupMac(id, mac) => lag.set_group_mac(id, mac),
+            LagOp::SetPortPriority(port, priority) => {
+                lag.set_port_priority(port, priority);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for LagOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LagOp::CreateGroup(id) => write!(f, "create group {}", id),
+            LagOp::DeleteGroup(id) => write!(f, "delete group {}", id),
+            LagOp::AddMember(id, port) => write!(f, "add port {} to group {}", port, id),
+            LagOp::RemoveMember(id, port) => write!(f, "remove port {} from group {}", port, id),
+            LagOp::SetHashPolicy(id, policy) => {
+                write!(f, "set group {} hash policy {}", id, policy)
+            }
+            LagOp::SetMinLinks(id, min_links) => {
+                write!(f, "set group {} min links {}", id, min_links)
+            }
+            LagOp::SetGroupSpeed(id, Some(speed)) => write!(f, "set group {} speed {}", id, speed),
+            LagOp::SetGroupSpeed(id, None) => write!(f, "clear group {} speed", id),
+            LagOp::SetGroupMac(id, Some(mac)) => write!(f, "set group {} mac {}", id, mac),
+            LagOp::SetGroupMac(id, None) => write!(f, "clear group {} mac", id),
+            LagOp::SetPortPriority(port, priority) => {
+                write!(f, "set port {} priority {}", port, priority)
+            }
+        }
+    }
+}
+
+/// A failed `LagTransaction`. The group manager was left as it was before
+/// the commit, so nothing needs to be rolled back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LagTransactionError {
+    /// Index of the failed step.
+    pub step: usize,
+    pub op: LagOp,
+    pub error: LagError,
+}
+
+impl fmt::Display for LagTransactionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Step {} failed to {}: {}; no change was made",
+            self.step + 1,
+            self.op,
+            self.error
+        )
+    }
+}
+
+impl Error for LagTransactionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+pub type LagTransactionResult<T> = Result<T, LagTransactionError>;
+
+/// Group manager changes applied in order by `commit`, all or none of them.
+/// The steps run on a copy of the manager that replaces it once every step
+/// succeeded.
+pub struct LagTransaction<'a> {
+    lag: &'a mut LagManager,
+    ops: Vec<LagOp>,
+}
+
+impl<'a> LagTransaction<'a> {
+    pub(crate) fn new(lag: &'a mut LagManager) -> Self {
+        LagTransaction {
+            lag,
+            ops: Vec::new(),
+        }
+    }
+
+    pub fn stage(&mut self, op: LagOp) -> &mut Self {
+        self.ops.push(op);
+        self
+    }
+
+    pub fn ops(&self) -> &[LagOp] {
+        &self.ops
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    pub fn commit(self) -> LagTransactionResult<()> {
+        let mut staged = self.lag.clone();
+        for (step, op) in self.ops.iter().enumerate() {
+            op.apply(&mut staged).map_err(|error| LagTransactionError {
+                step,
+                op: *op,
+                error,
+            })?;
+        }
+        *self.lag = staged;
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
 pub mod ffi;
 pub mod intf;
 pub mod lac;
+pub mod sdk;
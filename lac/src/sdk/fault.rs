@@ -0,0 +1,199 @@
+use super::*;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SdkOp {
+    Init,
+    SetLinkStatusHandler,
+    SetMac,
+}
+
+impl fmt::Display for SdkOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SdkOp::Init => write!(f, "init"),
+            SdkOp::SetLinkStatusHandler => write!(f, "set_link_status_handler"),
+            SdkOp::SetMac => write!(f, "set_mac"),
+        }
+    }
+}
+
+/// Which of the calls matching a fault fail. Calls are counted from 1.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FaultTrigger {
+    Always,
+    /// Only the n-th call.
+    Call(u64),
+    /// The first n calls.
+    First(u64),
+    /// Every call after the first n.
+    After(u64),
+}
+
+impl FaultTrigger {
+    fn fires(&self, call: u64) -> bool {
+        match *self {
+            FaultTrigger::Always => true,
+            FaultTrigger::Call(n) => call == n,
+            FaultTrigger::First(n) => call <= n,
+            FaultTrigger::After(n) => call > n,
+        }
+    }
+}
+
+/// A scripted SDK failure.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Fault {
+    pub op: SdkOp,
+    /// Restricts the fault to calls on this port. Ignored by operations
+    /// without a port.
+    pub port: Option<PhyPortId>,
+    pub trigger: FaultTrigger,
+    pub error: ChipSdkError,
+}
+
+impl Fault {
+    pub fn new(op: SdkOp, error: ChipSdkError) -> Self {
+        Fault {
+            op,
+            port: None,
+            trigger: FaultTrigger::Always,
+            error,
+        }
+    }
+
+    pub fn on_port(mut self, port: PhyPortId) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    pub fn trigger(mut self, trigger: FaultTrigger) -> Self {
+        self.trigger = trigger;
+        self
+    }
+
+    fn matches(&self, op: SdkOp, port: Option<&PhyPortId>) -> bool {
+        let port_matches = match (&self.port, port) {
+            (Some(fault_port), Some(port)) => fault_port == port,
+            _ => true,
+        };
+        self.op == op && port_matches
+    }
+}
+
+#[derive(Default)]
+struct FaultState {
+    faults: Vec<(Fault, u64)>,
+    latency: HashMap<SdkOp, Duration>,
+    calls: HashMap<SdkOp, u64>,
+    handler: Option<LinkStatusSink>,
+}
+
+/// Wraps another SDK and fails, delays or disturbs its calls as scripted.
+/// Clones share the script, so a test can keep one while a `Device` owns
+/// another.
+#[derive(Clone)]
+pub struct FaultySdk<S: ChipSdk> {
+    inner: S,
+    state: Arc<Mutex<FaultState>>,
+}
+
+impl<S: ChipSdk> FaultySdk<S> {
+    pub fn new(inner: S) -> Self {
+        FaultySdk {
+            inner,
+            state: Arc::default(),
+        }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Adds a fault. When several faults fire on the same call the one
+    /// injected first wins.
+    pub fn inject(&self, fault: Fault) {
+        self.state.lock().unwrap().faults.push((fault, 0));
+    }
+
+    /// Removes all faults and latencies.
+    pub fn clear(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.faults.clear();
+        state.latency.clear();
+    }
+
+    pub fn set_latency(&self, op: SdkOp, latency: Duration) {
+        self.state.lock().unwrap().latency.insert(op, latency);
+    }
+
+    /// Number of calls of `op` made so far, failed ones included.
+    pub fn calls(&self, op: SdkOp) -> u64 {
+        self.state
+            .lock()
+            .unwrap()
+            .calls
+            .get(&op)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Reports `count` down/up transitions of `port` to the installed link
+    /// status handler without touching the wrapped SDK.
+    pub fn flap(&self, port: &PhyPortId, count: usize) {
+        let handler = self.state.lock().unwrap().handler.clone();
+        if let Some(handler) = handler {
+            for _ in 0..count {
+                handler(*port, LinkStatus::LINK_DOWN);
+                handler(*port, LinkStatus::LINK_UP);
+            }
+        }
+    }
+
+    fn check(&self, op: SdkOp, port: Option<&PhyPortId>) -> SdkResult {
+        let (latency, error) = {
+            let mut state = self.state.lock().unwrap();
+            *state.calls.entry(op).or_insert(0) += 1;
+            let mut error = None;
+            for (fault, calls) in state.faults.iter_mut() {
+                if fault.matches(op, port) {
+                    *calls += 1;
+                    if error.is_none() && fault.trigger.fires(*calls) {
+                        error = Some(fault.error);
+                    }
+                }
+            }
+            (state.latency.get(&op).copied(), error)
+        };
+        if let Some(latency) = latency {
+            thread::sleep(latency);
+        }
+        match error {
+            Some(error) => Err(error),
+            None => SDK_OK,
+        }
+    }
+}
+
+impl<S: ChipSdk> ChipSdk for FaultySdk<S> {
+    fn init(&self, chips: &mut [SwitchChip], chip_num: &mut i32) -> SdkResult {
+        self.check(SdkOp::Init, None)?;
+        self.inner.init(chips, chip_num)
+    }
+
+    fn set_link_status_handler(&self, handler: LinkStatusSink) -> SdkResult {
+        self.check(SdkOp::SetLinkStatusHandler, None)?;
+        self.inner.set_link_status_handler(Arc::clone(&handler))?;
+        self.state.lock().unwrap().handler = Some(handler);
+        SDK_OK
+    }
+
+    fn set_mac(&self, phy_port_id: &PhyPortId, mac: &Mac) -> SdkResult {
+        self.check(SdkOp::SetMac, Some(phy_port_id))?;
+        self.inner.set_mac(phy_port_id, mac)
+    }
+}
@@ -0,0 +1,32 @@
+use super::*;
+use std::os::raw::c_int;
+use std::sync::Mutex;
+
+static LINK_STATUS_HANDLER: Mutex<Option<LinkStatusSink>> = Mutex::new(None);
+
+extern "C" fn link_status_trampoline(chip_id: c_int, port_id: c_int, status: LinkStatus) {
+    let handler = LINK_STATUS_HANDLER.lock().unwrap().clone();
+    if let Some(handler) = handler {
+        handler(PhyPortId(chip_id, port_id), status);
+    }
+}
+
+/// The vendor chip SDK linked through `crate::ffi`. The SDK keeps a single
+/// process-wide link status callback, so the last installed handler wins.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct FfiSdk;
+
+impl ChipSdk for FfiSdk {
+    fn init(&self, chips: &mut [SwitchChip], chip_num: &mut i32) -> SdkResult {
+        sdk_init(chips, chip_num)
+    }
+
+    fn set_link_status_handler(&self, handler: LinkStatusSink) -> SdkResult {
+        *LINK_STATUS_HANDLER.lock().unwrap() = Some(handler);
+        sdk_register_link_status_callback(link_status_trampoline)
+    }
+
+    fn set_mac(&self, phy_port_id: &PhyPortId, mac: &Mac) -> SdkResult {
+        sdk_set_mac(phy_port_id, mac)
+    }
+}
@@ -0,0 +1,35 @@
+mod fault;
+mod ffi_sdk;
+mod sim;
+
+pub use fault::{Fault, FaultTrigger, FaultySdk, SdkOp};
+pub use ffi_sdk::FfiSdk;
+pub use sim::SimSdk;
+
+use crate::ffi::*;
+use std::sync::Arc;
+
+pub type LinkStatusSink = Arc<dyn Fn(PhyPortId, LinkStatus) + Send + Sync>;
+
+/// Operations `Device` needs from a switch chip SDK.
+pub trait ChipSdk: Send + Sync {
+    fn init(&self, chips: &mut [SwitchChip], chip_num: &mut i32) -> SdkResult;
+    /// Installs the handler receiving link status changes, replacing the
+    /// previous one.
+    fn set_link_status_handler(&self, handler: LinkStatusSink) -> SdkResult;
+    fn set_mac(&self, phy_port_id: &PhyPortId, mac: &Mac) -> SdkResult;
+}
+
+impl<S: ChipSdk + ?Sized> ChipSdk for Box<S> {
+    fn init(&self, chips: &mut [SwitchChip], chip_num: &mut i32) -> SdkResult {
+        (**self).init(chips, chip_num)
+    }
+
+    fn set_link_status_handler(&self, handler: LinkStatusSink) -> SdkResult {
+        (**self).set_link_status_handler(handler)
+    }
+
+    fn set_mac(&self, phy_port_id: &PhyPortId, mac: &Mac) -> SdkResult {
+        (**self).set_mac(phy_port_id, mac)
+    }
+}
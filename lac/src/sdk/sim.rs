@@ -0,0 +1,158 @@
+use super::*;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+#[derive(Default)]
+struct SimState {
+    chips: Vec<SwitchChip>,
+    macs: HashMap<PhyPortId, Mac>,
+    handler: Option<LinkStatusSink>,
+}
+
+impl SimState {
+    fn port_mut(&mut self, phy_port_id: &PhyPortId) -> Option<&mut PhyPort> {
+        self.chips
+            .iter_mut()
+            .find(|chip| chip.chip_id == phy_port_id.0)
+            .and_then(|chip| {
+                chip.ports[..chip.numOfPorts as usize]
+                    .iter_mut()
+                    .find(|port| port.port_id == phy_port_id.1)
+            })
+    }
+}
+
+/// In-memory chip SDK. Every instance owns its chips, MAC table and link
+/// status handler, so tests using it can run in parallel. Clones share the
+/// same simulated hardware.
+#[derive(Clone, Default)]
+pub struct SimSdk {
+    state: Arc<Mutex<SimState>>,
+}
+
+impl SimSdk {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds `chip_num` chips with `port_num` link-down ports of `speed`
+    /// megabits each, failing like `add_chip` on chips the SDK cannot have.
+    pub fn with_topology(
+        chip_num: usize,
+        port_num: usize,
+        speed: i32,
+    ) -> Result<Self, ChipSdkError> {
+        if port_num > CHIP_SDK_PHY_PORT_PER_CHIP {
+            return Err(ChipSdkError::CHIP_SDK_INVALID_PARAM);
+        }
+        let sim = Self::new();
+        for chip_id in 0..chip_num {
+            let mut chip = SwitchChip {
+                chip_id: chip_id as ChipId,
+                numOfPorts: port_num as i32,
+                ..Default::default()
+            };
+            for (port_id, port) in chip.ports[..port_num].iter_mut().enumerate() {
+                port.port_id = port_id as i32;
+                port.speed = speed;
+            }
+            sim.add_chip(chip)?;
+        }
+        Ok(sim)
+    }
+
+    pub fn add_chip(&self, chip: SwitchChip) -> SdkResult {
+        let mut state = self.state.lock().unwrap();
+        if state.chips.len() >= CHIP_SDK_CHIP_MAX {
+            return Err(ChipSdkError::CHIP_SDK_NO_RESOURCE);
+        }
+        if chip.numOfPorts < 0 || chip.numOfPorts as usize > CHIP_SDK_PHY_PORT_PER_CHIP {
+            return Err(ChipSdkError::CHIP_SDK_INVALID_PARAM);
+        }
+        state.chips.push(chip);
+        SDK_OK
+    }
+
+    /// Pulls a chip out, dropping the MACs of its ports.
+    pub fn remove_chip(&self, chip_id: ChipId) -> Option<SwitchChip> {
+        let mut state = self.state.lock().unwrap();
+        let index = state
+            .chips
+            .iter()
+            .position(|chip| chip.chip_id == chip_id)?;
+        state.macs.retain(|port, _| port.0 != chip_id);
+        Some(state.chips.remove(index))
+    }
+
+    pub fn set_port_speed(&self, phy_port_id: &PhyPortId, speed: i32) -> SdkResult {
+        let mut state = self.state.lock().unwrap();
+        let port = state
+            .port_mut(phy_port_id)
+            .ok_or(ChipSdkError::CHIP_SDK_INVALID_PARAM)?;
+        port.speed = speed;
+        SDK_OK
+    }
+
+    pub fn chip(&self, chip_id: ChipId) -> Option<SwitchChip> {
+        let state = self.state.lock().unwrap();
+        state
+            .chips
+            .iter()
+            .find(|chip| chip.chip_id == chip_id)
+            .copied()
+    }
+
+    pub fn port(&self, phy_port_id: &PhyPortId) -> Option<PhyPort> {
+        self.state.lock().unwrap().port_mut(phy_port_id).copied()
+    }
+
+    pub fn mac(&self, phy_port_id: &PhyPortId) -> Option<Mac> {
+        self.state.lock().unwrap().macs.get(phy_port_id).copied()
+    }
+
+    /// Changes the link status of a port and notifies the installed handler
+    /// if the status actually changed.
+    pub fn set_link_status(&self, phy_port_id: &PhyPortId, status: LinkStatus) -> SdkResult {
+        let handler = {
+            let mut state = self.state.lock().unwrap();
+            let port = state
+                .port_mut(phy_port_id)
+                .ok_or(ChipSdkError::CHIP_SDK_INVALID_PARAM)?;
+            if port.status == status {
+                return SDK_OK;
+            }
+            port.status = status;
+            state.handler.clone()
+        };
+        if let Some(handler) = handler {
+            handler(*phy_port_id, status);
+        }
+        SDK_OK
+    }
+}
+
+impl ChipSdk for SimSdk {
+    fn init(&self, chips: &mut [SwitchChip], chip_num: &mut i32) -> SdkResult {
+        let state = self.state.lock().unwrap();
+        if chips.len() < state.chips.len() {
+            return Err(ChipSdkError::CHIP_SDK_INVALID_PARAM);
+        }
+        chips[..state.chips.len()].copy_from_slice(&state.chips);
+        *chip_num = state.chips.len() as i32;
+        SDK_OK
+    }
+
+    fn set_link_status_handler(&self, handler: LinkStatusSink) -> SdkResult {
+        self.state.lock().unwrap().handler = Some(handler);
+        SDK_OK
+    }
+
+    fn set_mac(&self, phy_port_id: &PhyPortId, mac: &Mac) -> SdkResult {
+        let mut state = self.state.lock().unwrap();
+        if state.port_mut(phy_port_id).is_none() {
+            return Err(ChipSdkError::CHIP_SDK_INVALID_PARAM);
+        }
+        state.macs.insert(*phy_port_id, *mac);
+        SDK_OK
+    }
+}
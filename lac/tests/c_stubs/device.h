@@ -6,6 +6,7 @@
 #define DEVICE_H
 
 #include "chip_sdk.h"
+#include <cstddef>
 #include <unordered_map>
 #include <vector>
 
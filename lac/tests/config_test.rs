@@ -0,0 +1,173 @@
+mod device;
+use device::*;
+use lac::ffi::*;
+use lac::lac::*;
+
+const CONFIG: &str = r#"
+[[group]]
+id = 1
+members = ["0/0", "1/0"]
+hash_policy = "layer3+4"
+min_links = 2
+speed = "25G"
+mac = "02:00:00:00:10:01"
+
+[[group]]
+id = 2
+members = ["0/1"]
+
+[[port]]
+port = "0/1"
+mac = "02:00:00:00:20:01"
+
+[[port]]
+port = "1/0"
+priority = 100
+"#;
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_parse_and_round_trip() {
+        let config = LacConfig::from_toml(CONFIG).expect("Failed to parse config");
+        assert_eq!(config.groups.len(), 2);
+        let group = &config.groups[0];
+        assert_eq!(group.members, vec![PhyPortId(0, 0), PhyPortId(1, 0)]);
+        assert_eq!(group.hash_policy, HashPolicy::Layer3And4);
+        assert_eq!(group.speed, Some(PortSpeed::GBPS_25));
+        assert_eq!(config.groups[1].min_links, 1);
+        assert_eq!(config.ports[1].priority, Some(100));
+
+        let text = config.to_toml().expect("Failed to serialize config");
+        assert_eq!(LacConfig::from_toml(&text).ok(), Some(config));
+
+        assert!(matches!(
+            LacConfig::from_toml("[[group]]\nid = 1\nmembers = [\"0-1\"]"),
+            Err(ConfigError::Parse(_))
+        ));
+        assert!(matches!(
+            LacConfig::from_toml("[[group]]\nid = 1\ncolour = \"red\""),
+            Err(ConfigError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_against_topology() {
+        let (_, device) = activated(2, 2, 25000);
+        let topology = &device.topology();
+        let config = LacConfig::from_toml(CONFIG).expect("Failed to parse config");
+        config
+            .validate(topology)
+            .expect("Failed to validate config");
+
+        let mut unknown = config.clone();
+        unknown.groups[1].members.push(PhyPortId(2, 0));
+        assert!(matches!(
+            unknown.validate(topology),
+            Err(ConfigError::UnknownPort(PhyPortId(2, 0)))
+        ));
+
+        let mut shared = config.clone();
+        shared.groups[1].members.push(PhyPortId(0, 0));
+        assert!(matches!(
+            shared.validate(topology),
+            Err(ConfigError::Lag(LagError::PortAlreadyAggregated(
+                PhyPortId(0, 0),
+                1
+            )))
+        ));
+
+        let mut slow = config.clone();
+        slow.groups[0].speed = Some(PortSpeed::GBPS_10);
+        assert!(matches!(
+            slow.validate(topology),
+            Err(ConfigError::SpeedMismatch { .. })
+        ));
+
+        let mut conflict = config.clone();
+        conflict.ports[0].port = PhyPortId(0, 0);
+        assert!(matches!(
+            conflict.validate(topology),
+            Err(ConfigError::MacConflict(PhyPortId(0, 0), 1))
+        ));
+
+        let mut duplicate = config;
+        duplicate.ports[0].mac = duplicate.groups[0].mac;
+        assert!(matches!(
+            duplicate.validate(topology),
+            Err(ConfigError::DuplicateMac(_))
+        ));
+    }
+
+    #[test]
+    fn test_dry_run_and_apply() {
+        let (sim, device) = activated(2, 2, 25000);
+        let mut lag = LagManager::new();
+        lag.create_group(3).expect("Failed to create group");
+        let config = LacConfig::from_toml(CONFIG).expect("Failed to parse config");
+
+        let diff = config
+            .apply(&device, &mut lag, true)
+            .expect("Failed to diff config");
+        assert!(lag.group(1).is_none());
+        assert!(device.programmed_macs().is_empty());
+        let lines: Vec<String> = diff.changes.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "delete group 3",
+                "create group 1",
+                "add port 0/0 to group 1",
+                "add port 1/0 to group 1",
+                "create group 2",
+                "add port 0/1 to group 2",
+                "set group 1 hash policy layer3+4",
+                "set group 1 min links 2",
+                "set group 1 speed 25G",
+                "set group 1 mac 02:00:00:00:10:01",
+                "set port 1/0 priority 100",
+                "set port 0/0 mac 02:00:00:00:10:01",
+                "set port 0/1 mac 02:00:00:00:20:01",
+                "set port 1/0 mac 02:00:00:00:10:01",
+            ]
+        );
+
+        config
+            .apply(&device, &mut lag, false)
+            .expect("Failed to apply config");
+        assert!(lag.group(3).is_none());
+        assert_eq!(lag.group_of(&PhyPortId(1, 0)), Some(1));
+        assert_eq!(lag.port_priority(&PhyPortId(1, 0)), 100);
+        assert_eq!(
+            sim.mac(&PhyPortId(1, 0)).map(MacAddr::from),
+            "02:00:00:00:10:01".parse().ok()
+        );
+        assert!(config
+            .apply(&device, &mut lag, true)
+            .expect("Failed to diff config")
+            .is_empty());
+
+        assert_eq!(LacConfig::capture(&lag, &device), config);
+
+        let mut reset = config.clone();
+        reset.ports.retain(|port| port.priority.is_none());
+        let diff = reset
+            .apply(&device, &mut lag, false)
+            .expect("Failed to apply config");
+        assert_eq!(
+            diff.changes,
+            vec![ConfigChange::SetPortPriority(
+                PhyPortId(1, 0),
+                DEFAULT_PORT_PRIORITY
+            )]
+        );
+        assert_eq!(lag.port_priority(&PhyPortId(1, 0)), DEFAULT_PORT_PRIORITY);
+        assert!(reset
+            .apply(&device, &mut lag, true)
+            .expect("Failed to diff config")
+            .is_empty());
+    }
+}
@@ -0,0 +1,95 @@
+mod device;
+use device::*;
+use lac::ffi::*;
+use lac::lac::*;
+
+const MEMBERS: [PhyPortId; 4] = [
+    PhyPortId(0, 0),
+    PhyPortId(0, 1),
+    PhyPortId(2, 0),
+    PhyPortId(2, 1),
+];
+
+/// Group 1 spanning chips 0 and 2, and group 2 on chip 1 alone.
+fn groups() -> LagManager {
+    let mut lag = group(&MEMBERS, PortSpeed::GBPS_10, LinkStatus::LINK_UP);
+    lag.create_group(2).expect("Failed to create group");
+    lag.add_member(2, PhyPortId(1, 0))
+        .expect("Failed to add member");
+    lag.update_port_speed(PhyPortId(1, 0), PortSpeed::GBPS_10);
+    lag.update_link_status(PhyPortId(1, 0), LinkStatus::LINK_UP);
+    lag
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_chip_redundancy() {
+        let lag = groups();
+        let redundancy = lag.chip_redundancy(1).expect("Failed to get redundancy");
+        assert!(redundancy.spans_chips());
+        assert!(redundancy.is_redundant());
+        assert_eq!(
+            redundancy.chips,
+            vec![
+                ChipMembers {
+                    chip_id: 0,
+                    members: 2,
+                    up: 2
+                },
+                ChipMembers {
+                    chip_id: 2,
+                    members: 2,
+                    up: 2
+                }
+            ]
+        );
+
+        let single = lag.chip_redundancy(2).expect("Failed to get redundancy");
+        assert!(!single.spans_chips());
+        assert!(!single.is_redundant());
+        assert_eq!(
+            lag.chip_redundancy(3).err(),
+            Some(LagError::GroupNotFound(3))
+        );
+    }
+
+    #[test]
+    fn test_chip_failure_degrades_group() {
+        let mut lag = groups();
+        lag.update_link_status(PhyPortId(2, 0), LinkStatus::LINK_DOWN);
+        let status = lag.group_status(1).expect("Failed to get status");
+        assert_eq!(status.state, GroupState::Degraded);
+        assert!(status.violations.is_empty());
+
+        assert_eq!(lag.fail_chip(2), vec![1]);
+        let status = lag.group_status(1).expect("Failed to get status");
+        assert_eq!(status.state, GroupState::Degraded);
+        assert_eq!(status.violations, vec![PolicyViolation::ChipDown(2)]);
+        assert_eq!(
+            lag.active_members(1),
+            Ok(vec![PhyPortId(0, 0), PhyPortId(0, 1)])
+        );
+        let redundancy = lag.chip_redundancy(1).expect("Failed to get redundancy");
+        assert_eq!(redundancy.failed_chips(), vec![2]);
+        assert!(!redundancy.is_redundant());
+        assert_eq!(lag.group_state(2), Ok(GroupState::Up));
+
+        assert_eq!(lag.fail_chip(0), vec![1]);
+        let status = lag.group_status(1).expect("Failed to get status");
+        assert_eq!(status.state, GroupState::Down);
+        assert!(!status
+            .violations
+            .iter()
+            .any(|violation| matches!(violation, PolicyViolation::ChipDown(_))));
+
+        lag.update_link_status(PhyPortId(2, 1), LinkStatus::LINK_UP);
+        assert_eq!(
+            lag.group_status(1).map(|status| status.violations),
+            Ok(vec![PolicyViolation::ChipDown(0)])
+        );
+    }
+}
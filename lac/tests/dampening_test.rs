@@ -0,0 +1,186 @@
+use lac::ffi::*;
+use lac::lac::*;
+use lac::sdk::*;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const UP: LinkStatus = LinkStatus::LINK_UP;
+const DOWN: LinkStatus = LinkStatus::LINK_DOWN;
+
+fn secs(secs: u64) -> Duration {
+    Duration::from_secs(secs)
+}
+
+fn damped_device() -> (
+    SimSdk,
+    ManualClock,
+    Device<SimSdk>,
+    Arc<Mutex<Vec<LinkStatus>>>,
+) {
+    let sim = SimSdk::with_topology(1, 2, 10000).expect("Failed to build simulator");
+    let clock = ManualClock::new();
+    let device = Device::with_sdk(sim.clone());
+    device.set_link_clock(clock.clone());
+    device.activate().expect("Failed to activate device");
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let events = Arc::clone(&received);
+    device
+        .subscribe_link_status(Box::new(move |_, status| {
+            events.lock().unwrap().push(status);
+        }))
+        .expect("Failed to subscribe");
+    (sim, clock, device, received)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_hold_timers() {
+        let port = PhyPortId(0, 0);
+        let start = Instant::now();
+        let mut damper = LinkDamper::new();
+        damper.set_hold_timers(HoldTimers::new(secs(2), secs(1)));
+        damper.set_port_hold_timers(PhyPortId(0, 1), Some(HoldTimers::default()));
+
+        assert_eq!(damper.report(port, UP, start), None);
+        assert_eq!(damper.next_deadline(), Some(start + secs(2)));
+        assert_eq!(damper.report(port, DOWN, start + secs(1)), None);
+        assert_eq!(damper.expire(start + secs(3)), vec![]);
+        assert_eq!(damper.next_deadline(), None);
+
+        assert_eq!(damper.report(port, UP, start + secs(4)), None);
+        assert_eq!(damper.expire(start + secs(5)), vec![]);
+        assert_eq!(damper.expire(start + secs(6)), vec![(port, UP)]);
+        assert_eq!(damper.report(port, UP, start + secs(7)), Some(UP));
+        assert_eq!(damper.report(port, DOWN, start + secs(8)), None);
+        assert_eq!(damper.expire(start + secs(9)), vec![(port, DOWN)]);
+
+        assert_eq!(damper.report(PhyPortId(0, 1), UP, start), Some(UP));
+        assert_eq!(damper.report(PhyPortId(0, 1), UP, start), Some(UP));
+    }
+
+    #[test]
+    fn test_penalty_suppression() {
+        let port = PhyPortId(0, 0);
+        let start = Instant::now();
+        let policy = DampeningPolicy::default();
+        let mut damper = LinkDamper::new();
+        damper
+            .set_policy(Some(policy))
+            .expect("Failed to set policy");
+
+        assert_eq!(damper.report(port, UP, start), Some(UP));
+        assert_eq!(damper.report(port, DOWN, start), Some(DOWN));
+        assert_eq!(damper.report(port, UP, start), Some(UP));
+        assert_eq!(damper.report(port, DOWN, start), Some(DOWN));
+        let status = damper.status(&port, start).expect("Missing damping status");
+        assert!(status.suppressed);
+        assert_eq!(status.penalty, 2000);
+        assert_eq!(damper.suppressed(), vec![port]);
+
+        assert_eq!(damper.report(port, UP, start + secs(1)), None);
+        let reuse = damper.next_deadline().expect("Missing reuse deadline");
+        assert!(reuse > start + policy.half_life && reuse < start + policy.half_life * 2);
+        assert_eq!(damper.expire(start + policy.half_life), vec![]);
+        assert_eq!(damper.expire(reuse + secs(1)), vec![(port, UP)]);
+        let status = damper.status(&port, reuse + secs(1)).unwrap();
+        assert!(!status.suppressed);
+        assert_eq!(status.stable, UP);
+
+        for _ in 0..20 {
+            damper.report(port, DOWN, reuse);
+            damper.report(port, UP, reuse);
+        }
+        let status = damper.status(&port, reuse).unwrap();
+        assert_eq!(status.penalty, 750 * 16);
+        assert_eq!(status.stable, DOWN);
+        assert_eq!(damper.expire(reuse + policy.max_suppress - secs(1)), vec![]);
+        assert_eq!(
+            damper.expire(reuse + policy.max_suppress + secs(1)),
+            vec![(port, UP)]
+        );
+    }
+
+    #[test]
+    fn test_invalid_policy_is_rejected() {
+        let mut damper = LinkDamper::new();
+        let policy = DampeningPolicy::default();
+        damper
+            .set_policy(Some(policy))
+            .expect("Failed to set policy");
+
+        let invalid = [
+            (
+                DampeningPolicy {
+                    half_life: Duration::ZERO,
+                    ..policy
+                },
+                DampeningError::ZeroHalfLife,
+            ),
+            (
+                DampeningPolicy {
+                    reuse_threshold: 0,
+                    ..policy
+                },
+                DampeningError::ZeroReuseThreshold,
+            ),
+            (
+                DampeningPolicy {
+                    reuse_threshold: 2000,
+                    ..policy
+                },
+                DampeningError::ReuseNotBelowSuppress {
+                    reuse: 2000,
+                    suppress: 2000,
+                },
+            ),
+        ];
+        for (invalid, err) in invalid {
+            assert_eq!(invalid.validate(), Err(err));
+            assert_eq!(damper.set_policy(Some(invalid)), Err(err));
+            assert_eq!(damper.policy(), Some(&policy));
+        }
+        damper.set_policy(None).expect("Failed to clear policy");
+        assert_eq!(damper.policy(), None);
+    }
+
+    #[test]
+    fn test_device_delivers_stable_changes() {
+        let (sim, clock, device, received) = damped_device();
+        let port = PhyPortId(0, 0);
+        device.set_hold_timers(HoldTimers::new(secs(3), Duration::ZERO));
+        device
+            .set_dampening(Some(DampeningPolicy {
+                suppress_threshold: 1500,
+                ..Default::default()
+            }))
+            .expect("Failed to set dampening");
+
+        sim.set_link_status(&port, UP).unwrap();
+        clock.advance(secs(1));
+        sim.set_link_status(&port, DOWN).unwrap();
+        sim.set_link_status(&port, UP).unwrap();
+        assert_eq!(device.damping_status(&port).unwrap().pending, Some(UP));
+        assert!(received.lock().unwrap().is_empty());
+        clock.advance(secs(3));
+        assert_eq!(device.run_link_timers(), 1);
+        assert_eq!(*received.lock().unwrap(), vec![UP]);
+
+        sim.set_link_status(&port, DOWN).unwrap();
+        assert_eq!(device.suppressed_ports(), vec![port]);
+        sim.set_link_status(&port, UP).unwrap();
+        assert_eq!(*received.lock().unwrap(), vec![UP, DOWN]);
+
+        let reuse = device.next_link_deadline().expect("Missing reuse deadline");
+        clock.advance(reuse - clock.now() + secs(3));
+        assert_eq!(device.run_link_timers(), 0);
+        clock.advance(secs(3));
+        let timers = device.link_timers();
+        assert_eq!(timers.run(), 1);
+        assert_eq!(*received.lock().unwrap(), vec![UP, DOWN, UP]);
+        assert!(device.suppressed_ports().is_empty());
+    }
+}
@@ -1,8 +1,18 @@
+// Fixtures shared by the integration tests. Every test binary compiles this
+// module but uses only part of it, hence the `dead_code` allowances.
+
 mod bindings;
 use bindings::*;
 
-pub use lac::lac::Device;
+pub use lac::lac::{Device, LagManager, MacAddr, PortSpeed};
+use lac::sdk::{ChipSdk, SimSdk};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
 
+#[allow(dead_code)]
 pub trait ChipFixture {
     fn new(id: ChipId) -> Self;
     fn add_port(&mut self, port: PhyPort) -> SdkResult;
@@ -37,16 +47,18 @@ macro_rules! ptr_to_option {
     };
 }
 
+#[allow(dead_code)]
 pub struct DeviceFixture {
     pub activated: bool,
 }
 
+#[allow(dead_code)]
 impl DeviceFixture {
     pub fn new() -> Self {
         DeviceFixture { activated: false }
     }
 
-    pub fn activate(&mut self, device: &mut Device) -> SdkResult {
+    pub fn activate(&mut self, device: &Device) -> SdkResult {
         assert!(!self.activated, "Device should be activated only once");
         device.activate().expect("Failed to activate device");
         self.activated = true;
@@ -88,3 +100,96 @@ impl DeviceFixture {
         ptr_to_option!(mac)
     }
 }
+
+/// Unicast, locally administered MAC ending in `last`.
+#[allow(dead_code)]
+pub fn mac(last: u8) -> MacAddr {
+    MacAddr::new([0x02, 0, 0, 0, 0, last])
+}
+
+/// Chip `chip_id` with the ports `port_ids`, all link down at `speed`
+/// megabits.
+#[allow(dead_code)]
+pub fn chip(chip_id: ChipId, port_ids: &[i32], speed: i32) -> SwitchChip {
+    let mut chip = SwitchChip {
+        chip_id,
+        numOfPorts: port_ids.len() as i32,
+        ..Default::default()
+    };
+    for (port, port_id) in chip.ports.iter_mut().zip(port_ids) {
+        port.port_id = *port_id;
+        port.speed = speed;
+    }
+    chip
+}
+
+/// Every port the SDK can report.
+#[allow(dead_code)]
+pub fn all_ports() -> impl Iterator<Item = PhyPortId> {
+    (0..CHIP_SDK_CHIP_MAX as i32).flat_map(|chip_id| {
+        (0..CHIP_SDK_PHY_PORT_PER_CHIP as i32).map(move |port_id| PhyPortId(chip_id, port_id))
+    })
+}
+
+/// Activated device on `sdk`.
+#[allow(dead_code)]
+pub fn activated_on<S: ChipSdk + Clone>(sdk: &S) -> Device<S> {
+    let device = Device::with_sdk(sdk.clone());
+    device.activate().expect("Failed to activate device");
+    device
+}
+
+/// Simulator with `chip_num` chips of `port_num` ports running at `speed`
+/// megabits, and an active device on it.
+#[allow(dead_code)]
+pub fn activated(chip_num: usize, port_num: usize, speed: i32) -> (SimSdk, Device<SimSdk>) {
+    let sim = SimSdk::with_topology(chip_num, port_num, speed).expect("Failed to build simulator");
+    let device = activated_on(&sim);
+    (sim, device)
+}
+
+/// Group manager with group 1 holding `members`, each running at `speed`
+/// with link `status`.
+#[allow(dead_code)]
+pub fn group(members: &[PhyPortId], speed: PortSpeed, status: LinkStatus) -> LagManager {
+    let mut lag = LagManager::new();
+    lag.create_group(1).expect("Failed to create group");
+    for port in members {
+        lag.add_member(1, *port).expect("Failed to add member");
+        lag.update_port_speed(*port, speed);
+        lag.update_link_status(*port, status);
+    }
+    lag
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Blocks the thread until `stream` yields.
+#[allow(dead_code)]
+pub fn next_event<S: futures_core::Stream + Unpin>(stream: &mut S) -> Option<S::Item> {
+    struct Next<'a, S>(&'a mut S);
+
+    impl<S: futures_core::Stream + Unpin> Future for Next<'_, S> {
+        type Output = Option<S::Item>;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            Pin::new(&mut *self.0).poll_next(cx)
+        }
+    }
+
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut next = Next(stream);
+    loop {
+        if let Poll::Ready(item) = Pin::new(&mut next).poll(&mut cx) {
+            return item;
+        }
+        thread::park();
+    }
+}
@@ -0,0 +1,155 @@
+mod device;
+use device::*;
+use lac::ffi::*;
+use lac::lac::MacAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+const CHIP_NUM: i32 = 2;
+const PORT_NUM: i32 = 4;
+const FLAPS: usize = 500;
+
+fn assert_send_sync<T: Send + Sync + Clone>() {}
+
+fn ports() -> impl Iterator<Item = PhyPortId> {
+    (0..CHIP_NUM).flat_map(|chip_id| (0..PORT_NUM).map(move |port_id| PhyPortId(chip_id, port_id)))
+}
+
+fn status(flap: usize) -> LinkStatus {
+    if flap.is_multiple_of(2) {
+        LinkStatus::LINK_UP
+    } else {
+        LinkStatus::LINK_DOWN
+    }
+}
+
+fn mac(port: &PhyPortId, round: usize) -> MacAddr {
+    MacAddr::new([
+        0x02,
+        0,
+        round as u8,
+        (round >> 8) as u8,
+        port.0 as u8,
+        port.1 as u8,
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_concurrent_device_access() {
+        assert_send_sync::<Device>();
+
+        let mut fixture = DeviceFixture::new();
+        for chip_id in 0..CHIP_NUM {
+            let mut chip = SwitchChip::new(chip_id);
+            for port_id in 0..PORT_NUM {
+                chip.add_port(PhyPort {
+                    port_id,
+                    speed: 10000,
+                    ..Default::default()
+                })
+                .expect("Failed to add port");
+            }
+            fixture.add_chip(chip).expect("Failed to add chip");
+        }
+        let device = Device::new();
+        fixture.activate(&device).expect("Failed to setup device");
+
+        // Link changes reach the topology without any subscriber.
+        thread::scope(|scope| {
+            for port in ports() {
+                let fixture = &fixture;
+                scope.spawn(move || {
+                    for flap in 0..=FLAPS {
+                        fixture
+                            .set_link_status(&port, status(flap))
+                            .expect("Failed to set link status");
+                    }
+                });
+            }
+            let device = device.clone();
+            scope.spawn(move || {
+                for _ in 0..FLAPS {
+                    assert_eq!(device.ports().count(), (CHIP_NUM * PORT_NUM) as usize);
+                }
+            });
+        });
+        for port in ports() {
+            assert_eq!(
+                device.port(&port).map(|port| port.status),
+                Some(LinkStatus::LINK_UP)
+            );
+            fixture
+                .set_link_status(&port, LinkStatus::LINK_DOWN)
+                .expect("Failed to set link status");
+        }
+        assert!(device.ports().all(|port| !port.is_up()));
+
+        let events = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&events);
+        let reader = device.clone();
+        let _subscription = device
+            .subscribe_link_status(Box::new(move |port, _| {
+                // Handlers may query the device they are called from.
+                assert!(reader.port(&port).is_some());
+                counter.fetch_add(1, Ordering::SeqCst);
+            }))
+            .expect("Failed to subscribe");
+
+        thread::scope(|scope| {
+            for port in ports() {
+                let fixture = &fixture;
+                scope.spawn(move || {
+                    for flap in 0..FLAPS {
+                        fixture
+                            .set_link_status(&port, status(flap))
+                            .expect("Failed to set link status");
+                    }
+                });
+            }
+            for chip_id in 0..CHIP_NUM {
+                let device = device.clone();
+                scope.spawn(move || {
+                    for round in 0..FLAPS {
+                        for port_id in 0..PORT_NUM {
+                            let port = PhyPortId(chip_id, port_id);
+                            device
+                                .set_mac(&port, mac(&port, round))
+                                .expect("Failed to set mac");
+                        }
+                    }
+                });
+            }
+            for _ in 0..2 {
+                let device = device.clone();
+                scope.spawn(move || {
+                    for _ in 0..FLAPS {
+                        assert_eq!(device.ports().count(), (CHIP_NUM * PORT_NUM) as usize);
+                        assert!(device.programmed_macs().len() <= (CHIP_NUM * PORT_NUM) as usize);
+                        let topology = device.topology();
+                        assert_eq!(topology.chips().count(), CHIP_NUM as usize);
+                    }
+                });
+            }
+        });
+
+        assert_eq!(
+            events.load(Ordering::SeqCst),
+            FLAPS * (CHIP_NUM * PORT_NUM) as usize
+        );
+        for port in ports() {
+            assert_eq!(
+                device.port(&port).map(|port| port.status),
+                Some(LinkStatus::LINK_DOWN)
+            );
+            let programmed = fixture.get_mac_addr(&port).map(|mac| MacAddr::from(*mac));
+            assert_eq!(programmed, Some(mac(&port, FLAPS - 1)));
+            assert_eq!(device.mac(&port), programmed);
+        }
+    }
+}
@@ -0,0 +1,160 @@
+mod device;
+use device::*;
+use lac::ffi::*;
+use lac::lac::*;
+use lac::sdk::*;
+use std::time::{Duration, Instant};
+
+fn faulty(chip_num: usize, port_num: usize) -> FaultySdk<SimSdk> {
+    FaultySdk::new(
+        SimSdk::with_topology(chip_num, port_num, 10000).expect("Failed to build simulator"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_activate_recovers_from_busy() {
+        let sdk = faulty(2, 2);
+        sdk.inject(
+            Fault::new(SdkOp::Init, ChipSdkError::CHIP_SDK_BUSY).trigger(FaultTrigger::First(2)),
+        );
+        let device = Device::with_sdk(sdk.clone());
+
+        for _ in 0..2 {
+            assert_eq!(
+                device.activate(),
+                Err(DeviceError::Sdk(ChipSdkError::CHIP_SDK_BUSY))
+            );
+            assert!(device.chips().next().is_none());
+        }
+        device.activate().expect("Failed to activate device");
+        assert_eq!(device.chips().count(), 2);
+        assert_eq!(sdk.calls(SdkOp::Init), 3);
+    }
+
+    #[test]
+    fn test_set_mac_faults_per_port_and_call() {
+        let sdk = faulty(1, 3);
+        sdk.inject(
+            Fault::new(SdkOp::SetMac, ChipSdkError::CHIP_SDK_TIMEOUT)
+                .on_port(PhyPortId(0, 1))
+                .trigger(FaultTrigger::Call(2)),
+        );
+        sdk.inject(
+            Fault::new(SdkOp::SetMac, ChipSdkError::CHIP_SDK_NO_RESOURCE).on_port(PhyPortId(0, 2)),
+        );
+        let device = activated_on(&sdk);
+
+        device
+            .set_mac(&PhyPortId(0, 1), mac(1))
+            .expect("Failed to set mac");
+        assert_eq!(
+            device.set_mac(&PhyPortId(0, 1), mac(2)),
+            Err(DeviceError::Sdk(ChipSdkError::CHIP_SDK_TIMEOUT))
+        );
+        assert_eq!(device.mac(&PhyPortId(0, 1)), Some(mac(1)));
+        device
+            .set_mac(&PhyPortId(0, 1), mac(2))
+            .expect("Failed to set mac");
+        assert_eq!(device.mac(&PhyPortId(0, 1)), Some(mac(2)));
+
+        assert_eq!(
+            device.set_mac(&PhyPortId(0, 2), mac(3)),
+            Err(DeviceError::Sdk(ChipSdkError::CHIP_SDK_NO_RESOURCE))
+        );
+        assert_eq!(device.mac(&PhyPortId(0, 2)), None);
+        assert!(sdk.inner().mac(&PhyPortId(0, 2)).is_none());
+        device
+            .set_mac(&PhyPortId(0, 0), mac(3))
+            .expect("Failed to set mac");
+
+        sdk.clear();
+        device
+            .set_mac(&PhyPortId(0, 2), mac(4))
+            .expect("Failed to set mac");
+        assert_eq!(sdk.calls(SdkOp::SetMac), 6);
+    }
+
+    #[test]
+    fn test_activate_retries_registration() {
+        let sdk = faulty(1, 1);
+        sdk.inject(
+            Fault::new(SdkOp::SetLinkStatusHandler, ChipSdkError::CHIP_SDK_ERROR)
+                .trigger(FaultTrigger::Call(1)),
+        );
+        let device = Device::with_sdk(sdk.clone());
+
+        assert_eq!(
+            device.activate(),
+            Err(DeviceError::Sdk(ChipSdkError::CHIP_SDK_ERROR))
+        );
+        assert_eq!(device.state(), DeviceState::Created);
+        assert_eq!(device.chips().count(), 0);
+        device.activate().expect("Failed to activate device");
+        let events = device.link_events(16).expect("Failed to subscribe");
+        sdk.inner()
+            .set_link_status(&PhyPortId(0, 0), LinkStatus::LINK_UP)
+            .expect("Failed to set link status");
+        assert_eq!(
+            events.try_recv().map(|event| event.status),
+            Some(LinkStatus::LINK_UP)
+        );
+
+        sdk.flap(&PhyPortId(0, 0), 2);
+        let statuses: Vec<_> = events.try_iter().map(|event| event.status).collect();
+        assert_eq!(
+            statuses,
+            [LinkStatus::LINK_DOWN, LinkStatus::LINK_UP].repeat(2)
+        );
+        assert_eq!(
+            sdk.inner().port(&PhyPortId(0, 0)).map(|port| port.status),
+            Some(LinkStatus::LINK_UP)
+        );
+    }
+
+    #[test]
+    fn test_port_ignored_without_port() {
+        let sdk = faulty(1, 1);
+        sdk.inject(
+            Fault::new(SdkOp::Init, ChipSdkError::CHIP_SDK_NOT_FOUND)
+                .on_port(PhyPortId(0, 0))
+                .trigger(FaultTrigger::Call(1)),
+        );
+        sdk.inject(
+            Fault::new(
+                SdkOp::SetLinkStatusHandler,
+                ChipSdkError::CHIP_SDK_NO_MEMORY,
+            )
+            .on_port(PhyPortId(0, 0))
+            .trigger(FaultTrigger::Call(1)),
+        );
+        let device = Device::with_sdk(sdk.clone());
+
+        assert_eq!(
+            device.activate(),
+            Err(DeviceError::Sdk(ChipSdkError::CHIP_SDK_NOT_FOUND))
+        );
+        assert_eq!(
+            device.activate(),
+            Err(DeviceError::Sdk(ChipSdkError::CHIP_SDK_NO_MEMORY))
+        );
+        device.activate().expect("Failed to activate device");
+        assert_eq!(sdk.calls(SdkOp::Init), 3);
+        assert_eq!(sdk.calls(SdkOp::SetLinkStatusHandler), 2);
+    }
+
+    #[test]
+    fn test_latency() {
+        let sdk = faulty(1, 1);
+        sdk.set_latency(SdkOp::Init, Duration::from_millis(20));
+        let device = Device::with_sdk(sdk);
+
+        let start = Instant::now();
+        device.activate().expect("Failed to activate device");
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+}
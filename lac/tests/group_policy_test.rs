@@ -0,0 +1,133 @@
+mod device;
+use device::*;
+use lac::ffi::*;
+use lac::lac::*;
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_min_links() {
+        let ports: Vec<_> = (0..3).map(|port_id| PhyPortId(0, port_id)).collect();
+        let mut lag = group(&ports, PortSpeed::GBPS_25, LinkStatus::LINK_UP);
+        lag.set_min_links(1, 2).expect("Failed to set min links");
+        assert_eq!(
+            lag.set_min_links(1, 0),
+            Err(LagError::InvalidMinLinks(1, 0))
+        );
+        assert_eq!(
+            lag.set_min_links(1, CHIP_SDK_PHY_PORT_PER_GROUP_MAX + 1),
+            Err(LagError::InvalidMinLinks(1, 5))
+        );
+        assert_eq!(lag.group(1).map(|group| group.min_links()), Some(2));
+        assert_eq!(lag.group_state(1), Ok(GroupState::Up));
+
+        lag.update_link_status(ports[0], LinkStatus::LINK_DOWN);
+        assert_eq!(lag.group_state(1), Ok(GroupState::Degraded));
+
+        lag.update_link_status(ports[1], LinkStatus::LINK_DOWN);
+        let status = lag.group_status(1).expect("Failed to get status");
+        assert_eq!(status.state, GroupState::Down);
+        assert_eq!(
+            status.violations,
+            vec![PolicyViolation::MinLinks {
+                required: 2,
+                active: 1
+            }]
+        );
+        assert_eq!(status.member_state(&ports[2]), Some(MemberState::Active));
+        assert_eq!(lag.active_members(1), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_speed_consistency() {
+        let ports: Vec<_> = (0..3).map(|port_id| PhyPortId(0, port_id)).collect();
+        let mut lag = group(&ports, PortSpeed::GBPS_25, LinkStatus::LINK_UP);
+        lag.update_port_speed(ports[2], PortSpeed::GBPS_10);
+
+        let status = lag.group_status(1).expect("Failed to get status");
+        assert_eq!(status.state, GroupState::Degraded);
+        assert_eq!(status.speed, Some(PortSpeed::GBPS_25));
+        assert_eq!(
+            status.member_state(&ports[2]),
+            Some(MemberState::SpeedMismatch)
+        );
+        assert_eq!(
+            status.violations,
+            vec![PolicyViolation::SpeedMismatch {
+                port: ports[2],
+                speed: PortSpeed::GBPS_10,
+                expected: PortSpeed::GBPS_25
+            }]
+        );
+        assert_eq!(lag.active_members(1), Ok(ports[..2].to_vec()));
+
+        lag.set_group_speed(1, Some(PortSpeed::GBPS_10))
+            .expect("Failed to set group speed");
+        let status = lag.group_status(1).expect("Failed to get status");
+        assert_eq!(status.members_in(MemberState::Active), vec![ports[2]]);
+        assert_eq!(status.violations.len(), 2);
+    }
+
+    #[test]
+    fn test_speed_follows_up_members() {
+        let ports: Vec<_> = (0..3).map(|port_id| PhyPortId(0, port_id)).collect();
+        let mut lag = group(&ports, PortSpeed::GBPS_25, LinkStatus::LINK_UP);
+        lag.set_port_priority(ports[0], 1);
+        lag.update_port_speed(ports[0], PortSpeed::GBPS_10);
+        lag.update_link_status(ports[0], LinkStatus::LINK_DOWN);
+
+        let status = lag.group_status(1).expect("Failed to get status");
+        assert_eq!(status.state, GroupState::Degraded);
+        assert_eq!(status.speed, Some(PortSpeed::GBPS_25));
+        assert_eq!(status.members_in(MemberState::Active), ports[1..].to_vec());
+        assert_eq!(
+            status.member_state(&ports[0]),
+            Some(MemberState::SpeedMismatch)
+        );
+
+        for port in &ports[1..] {
+            lag.update_link_status(*port, LinkStatus::LINK_DOWN);
+        }
+        let status = lag.group_status(1).expect("Failed to get status");
+        assert_eq!(status.speed, None);
+        assert_eq!(status.members_in(MemberState::Down), ports);
+        assert_eq!(
+            status.violations,
+            vec![PolicyViolation::MinLinks {
+                required: 1,
+                active: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn test_standby_by_priority() {
+        let ports: Vec<_> = (0..6).map(|port_id| PhyPortId(0, port_id)).collect();
+        let mut lag = group(&ports, PortSpeed::GBPS_25, LinkStatus::LINK_UP);
+        lag.set_port_priority(ports[5], 10);
+        lag.set_port_priority(ports[0], 0xffff);
+
+        let status = lag.group_status(1).expect("Failed to get status");
+        assert_eq!(status.state, GroupState::Up);
+        assert_eq!(
+            status.members_in(MemberState::Active),
+            vec![ports[1], ports[2], ports[3], ports[5]]
+        );
+        assert_eq!(
+            status.members_in(MemberState::Standby),
+            vec![ports[0], ports[4]]
+        );
+
+        lag.update_link_status(ports[2], LinkStatus::LINK_DOWN);
+        let status = lag.group_status(1).expect("Failed to get status");
+        assert_eq!(status.state, GroupState::Degraded);
+        assert_eq!(
+            status.members_in(MemberState::Active),
+            vec![ports[1], ports[3], ports[4], ports[5]]
+        );
+        assert_eq!(status.members_in(MemberState::Standby), vec![ports[0]]);
+    }
+}
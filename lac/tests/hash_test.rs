@@ -0,0 +1,132 @@
+mod device;
+use device::*;
+use lac::ffi::*;
+use lac::lac::*;
+use std::net::{IpAddr, Ipv4Addr};
+
+fn flow(index: u32) -> FlowKey {
+    FlowKey {
+        src_mac: MacAddr::from_u64(0x0200_0000_0000 + (index % 7) as u64).unwrap(),
+        dst_mac: MacAddr::new([0x02, 0, 0, 0, 1, 0]),
+        src_ip: Some(IpAddr::V4(Ipv4Addr::from(0x0a00_0000 + index))),
+        dst_ip: Some(IpAddr::V4(Ipv4Addr::new(10, 1, 0, 1))),
+        protocol: 6,
+        src_port: 10000 + (index % 50000) as u16,
+        dst_port: 443,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_hash_fields() {
+        let base = flow(1);
+        let other_ip = FlowKey {
+            src_ip: flow(2).src_ip,
+            ..base
+        };
+        let other_mac = FlowKey {
+            src_mac: flow(2).src_mac,
+            ..base
+        };
+        let other_port = FlowKey {
+            src_port: 1,
+            ..base
+        };
+
+        assert_eq!(
+            HashPolicy::Layer2.hash(&base),
+            HashPolicy::Layer2.hash(&other_ip)
+        );
+        assert_ne!(
+            HashPolicy::Layer2.hash(&base),
+            HashPolicy::Layer2.hash(&other_mac)
+        );
+        assert_ne!(
+            HashPolicy::Layer2And3.hash(&base),
+            HashPolicy::Layer2And3.hash(&other_ip)
+        );
+        assert_eq!(
+            HashPolicy::Layer2And3.hash(&base),
+            HashPolicy::Layer2And3.hash(&other_port)
+        );
+        assert_eq!(
+            HashPolicy::Layer3And4.hash(&base),
+            HashPolicy::Layer3And4.hash(&other_mac)
+        );
+        assert_ne!(
+            HashPolicy::Layer3And4.hash(&base),
+            HashPolicy::Layer3And4.hash(&other_port)
+        );
+
+        let no_ip = FlowKey {
+            src_ip: None,
+            ..base
+        };
+        assert_eq!(
+            HashPolicy::Layer3And4.hash(&no_ip),
+            HashPolicy::Layer2.hash(&no_ip)
+        );
+    }
+
+    #[test]
+    fn test_rebalance_on_link_change() {
+        let members = [
+            PhyPortId(0, 0),
+            PhyPortId(0, 1),
+            PhyPortId(1, 0),
+            PhyPortId(1, 1),
+        ];
+        let mut lag = group(&members, PortSpeed::GBPS_10, LinkStatus::LINK_UP);
+        lag.set_hash_policy(1, HashPolicy::Layer3And4)
+            .expect("Failed to set hash policy");
+        let flows: Vec<FlowKey> = (0..4000).map(flow).collect();
+        let select = |lag: &LagManager| -> Vec<Option<PhyPortId>> {
+            flows
+                .iter()
+                .map(|flow| lag.select_member(1, flow).expect("Failed to select"))
+                .collect()
+        };
+
+        let before = select(&lag);
+        assert_eq!(before, select(&lag));
+        let distribution = lag
+            .flow_distribution(1, &flows)
+            .expect("Failed to get distribution");
+        assert_eq!(distribution.total(), 4000);
+        assert_eq!(distribution.unassigned(), 0);
+        assert!(distribution.imbalance() < 1.15, "{:?}", distribution);
+
+        lag.update_link_status(PhyPortId(0, 1), LinkStatus::LINK_DOWN);
+        let degraded = select(&lag);
+        for (old, new) in before.iter().zip(&degraded) {
+            assert_ne!(*new, Some(PhyPortId(0, 1)));
+            if *old != Some(PhyPortId(0, 1)) {
+                assert_eq!(old, new);
+            }
+        }
+        let distribution = lag
+            .flow_distribution(1, &flows)
+            .expect("Failed to get distribution");
+        assert_eq!(distribution.count(&PhyPortId(0, 1)), 0);
+        assert!(distribution.imbalance() < 1.15, "{:?}", distribution);
+
+        lag.update_link_status(PhyPortId(0, 1), LinkStatus::LINK_UP);
+        assert_eq!(select(&lag), before);
+
+        for port in members {
+            lag.update_link_status(port, LinkStatus::LINK_DOWN);
+        }
+        let distribution = lag
+            .flow_distribution(1, &flows)
+            .expect("Failed to get distribution");
+        assert_eq!(distribution.unassigned(), 4000);
+        assert_eq!(
+            lag.select_member(2, &flows[0]),
+            Err(LagError::GroupNotFound(2))
+        );
+    }
+}
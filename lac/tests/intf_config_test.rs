@@ -0,0 +1,49 @@
+use lac::ffi::*;
+use lac::intf::*;
+use lac::lac::*;
+use lac::sdk::*;
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_lac_init_applies_config() {
+        let sim = SimSdk::with_topology(1, 4, 10000).expect("Failed to build simulator");
+        let mut config = LacConfig::default();
+        let mut group = GroupConfig::new(1);
+        group.members = vec![PhyPortId(0, 0), PhyPortId(0, 1)];
+        group.mac = Some("02:00:00:00:00:01".parse().unwrap());
+        config.groups.push(group);
+
+        let mut invalid = config.clone();
+        invalid.groups[0].members.push(PhyPortId(0, 4));
+        assert_eq!(
+            lac_init_with_config(sim.clone(), Some(&invalid)),
+            Err(LacError::InvalidConfig(
+                "Port 0/4 does not exist".to_string()
+            ))
+        );
+        assert_eq!(lac_current_config(), Err(LacError::Uninitialized));
+
+        lac_init_with_config(sim.clone(), Some(&config)).expect("Failed to init lac");
+        assert_eq!(
+            sim.mac(&PhyPortId(0, 1)).map(MacAddr::from),
+            config.groups[0].mac
+        );
+        assert_eq!(lac_current_config(), Ok(config.clone()));
+
+        config.groups[0].members.push(PhyPortId(0, 2));
+        let diff = lac_apply_config(&config, true).expect("Failed to diff config");
+        assert_eq!(
+            diff.to_string(),
+            "add port 0/2 to group 1\nset port 0/2 mac 02:00:00:00:00:01\n"
+        );
+        assert!(sim.mac(&PhyPortId(0, 2)).is_none());
+
+        lac_apply_config(&config, false).expect("Failed to apply config");
+        assert!(sim.mac(&PhyPortId(0, 2)).is_some());
+        assert_eq!(lac_current_config(), Ok(config));
+    }
+}
@@ -0,0 +1,78 @@
+use lac::ffi::*;
+use lac::intf::*;
+use lac::lac::*;
+use lac::sdk::*;
+use std::sync::{Arc, Mutex};
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_lac_event_handler() {
+        assert_eq!(lac_set_event_handler(None), Err(LacError::Uninitialized));
+
+        let sim = SimSdk::with_topology(2, 1, 10000).expect("Failed to build simulator");
+        lac_init_with_sdk(sim.clone()).expect("Failed to init lac");
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let events = Arc::clone(&received);
+        lac_set_event_handler(Some(Box::new(move |event| {
+            // Handlers may call back into lac.
+            lac_telemetry().expect("Failed to read telemetry from handler");
+            events.lock().unwrap().push(*event);
+        })))
+        .expect("Failed to set event handler");
+
+        let mac = MacAddr::new([0x02, 0, 0, 0, 0, 0x20]);
+        let mut group = GroupConfig::new(1);
+        group.members = vec![PhyPortId(0, 0), PhyPortId(1, 0)];
+        group.mac = Some(mac);
+        let config = LacConfig {
+            groups: vec![group],
+            ..Default::default()
+        };
+        lac_apply_config(&config, false).expect("Failed to apply config");
+        assert_eq!(
+            received.lock().unwrap().drain(..).collect::<Vec<_>>(),
+            vec![
+                LagEvent::MacProgrammed {
+                    group: 1,
+                    port: PhyPortId(0, 0),
+                    mac,
+                },
+                LagEvent::MacProgrammed {
+                    group: 1,
+                    port: PhyPortId(1, 0),
+                    mac,
+                },
+            ]
+        );
+
+        sim.set_link_status(&PhyPortId(1, 0), LinkStatus::LINK_UP)
+            .expect("Failed to set link status");
+        assert_eq!(
+            received.lock().unwrap().drain(..).collect::<Vec<_>>(),
+            vec![
+                LagEvent::MemberSelected {
+                    group: 1,
+                    port: PhyPortId(1, 0),
+                },
+                LagEvent::GroupUp {
+                    group: 1,
+                    port: Some(PhyPortId(1, 0)),
+                },
+                LagEvent::MemberDistributing {
+                    group: 1,
+                    port: PhyPortId(1, 0),
+                },
+            ]
+        );
+
+        lac_set_event_handler(None).expect("Failed to remove event handler");
+        sim.set_link_status(&PhyPortId(1, 0), LinkStatus::LINK_DOWN)
+            .expect("Failed to set link status");
+        assert!(received.lock().unwrap().is_empty());
+        lac_deinit().expect("Failed to deinit lac");
+    }
+}
@@ -0,0 +1,78 @@
+use lac::ffi::*;
+use lac::intf::*;
+use lac::sdk::*;
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_lac_init_recovers_from_sdk_faults() {
+        let sdk =
+            FaultySdk::new(SimSdk::with_topology(1, 2, 25000).expect("Failed to build simulator"));
+        sdk.inject(
+            Fault::new(SdkOp::Init, ChipSdkError::CHIP_SDK_TIMEOUT).trigger(FaultTrigger::Call(1)),
+        );
+        sdk.inject(
+            Fault::new(SdkOp::SetLinkStatusHandler, ChipSdkError::CHIP_SDK_BUSY)
+                .trigger(FaultTrigger::Call(1)),
+        );
+
+        assert_eq!(
+            lac_init_with_sdk(sdk.clone()),
+            Err(LacError::Sdk {
+                op: SdkOp::Init,
+                port: None,
+                source: ChipSdkError::CHIP_SDK_TIMEOUT
+            })
+        );
+        assert_eq!(lac_query_chip_info(), Err(LacError::Uninitialized));
+        assert_eq!(
+            lac_init_with_sdk(sdk.clone()),
+            Err(LacError::Sdk {
+                op: SdkOp::SetLinkStatusHandler,
+                port: None,
+                source: ChipSdkError::CHIP_SDK_BUSY
+            })
+        );
+        assert_eq!(lac_query_chip_info(), Err(LacError::Uninitialized));
+
+        lac_init_with_sdk(sdk.clone()).expect("Failed to init lac");
+        sdk.inner()
+            .set_link_status(&PhyPortId(0, 1), LinkStatus::LINK_UP)
+            .expect("Failed to set link status");
+        let chips = lac_query_chip_info().expect("Failed to query chip info");
+        assert_eq!(chips[0].ports[1].status, LinkStatus::LINK_UP);
+
+        sdk.flap(&PhyPortId(0, 0), 1);
+        let chips = lac_query_chip_info().expect("Failed to query chip info");
+        assert_eq!(chips[0].ports[0].status, LinkStatus::LINK_UP);
+
+        // A failed restart keeps the running device.
+        let broken =
+            FaultySdk::new(SimSdk::with_topology(2, 2, 25000).expect("Failed to build simulator"));
+        broken.inject(Fault::new(SdkOp::Init, ChipSdkError::CHIP_SDK_ERROR));
+        assert!(lac_init_with_sdk(broken).is_err());
+        sdk.inner()
+            .set_link_status(&PhyPortId(0, 1), LinkStatus::LINK_DOWN)
+            .expect("Failed to set link status");
+        let chips = lac_query_chip_info().expect("Failed to query chip info");
+        assert_eq!(chips.len(), 1);
+        assert_eq!(chips[0].ports[1].status, LinkStatus::LINK_DOWN);
+
+        // Once replaced, the old device no longer reaches lac.
+        let replacement = SimSdk::with_topology(1, 2, 25000).expect("Failed to build simulator");
+        lac_init_with_sdk(replacement.clone()).expect("Failed to init lac");
+        sdk.inner()
+            .set_link_status(&PhyPortId(0, 1), LinkStatus::LINK_UP)
+            .expect("Failed to set link status");
+        replacement
+            .set_link_status(&PhyPortId(0, 0), LinkStatus::LINK_UP)
+            .expect("Failed to set link status");
+        let chips = lac_query_chip_info().expect("Failed to query chip info");
+        assert_eq!(chips[0].ports[0].status, LinkStatus::LINK_UP);
+        assert_eq!(chips[0].ports[1].status, LinkStatus::LINK_DOWN);
+        lac_deinit().expect("Failed to deinit lac");
+    }
+}
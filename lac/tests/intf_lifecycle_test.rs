@@ -0,0 +1,33 @@
+use lac::ffi::*;
+use lac::intf::*;
+use lac::lac::DeviceState;
+use lac::sdk::*;
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_lac_deinit() {
+        assert_eq!(lac_deinit(), Err(LacError::Uninitialized));
+
+        let sim = SimSdk::with_topology(1, 2, 10000).expect("Failed to build simulator");
+        lac_init_with_sdk(sim.clone()).expect("Failed to init lac");
+        lac_deinit().expect("Failed to deinit lac");
+        assert_eq!(lac_query_chip_info(), Err(LacError::Uninitialized));
+        assert_eq!(lac_deinit(), Err(LacError::Uninitialized));
+        // Link changes after shutdown reach nobody.
+        sim.set_link_status(&PhyPortId(0, 0), LinkStatus::LINK_UP)
+            .expect("Failed to set link status");
+
+        lac_init_with_sdk(sim.clone()).expect("Failed to init lac");
+        lac_init_with_sdk(sim.clone()).expect("Failed to init lac again");
+        sim.set_link_status(&PhyPortId(0, 1), LinkStatus::LINK_UP)
+            .expect("Failed to set link status");
+        let chips = lac_query_chip_info().expect("Failed to query chip info");
+        assert_eq!(chips[0].ports[1].status, LinkStatus::LINK_UP);
+        assert_eq!(LacError::InvalidState(DeviceState::ShutDown).code(), 5);
+        lac_deinit().expect("Failed to deinit lac");
+    }
+}
@@ -0,0 +1,39 @@
+use lac::ffi::*;
+use lac::intf::*;
+use lac::lac::*;
+use lac::sdk::*;
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_lac_rescan() {
+        assert_eq!(lac_rescan(), Err(LacError::Uninitialized));
+
+        let sim = SimSdk::with_topology(2, 1, 10000).expect("Failed to build simulator");
+        let mac = MacAddr::new([0x02, 0, 0, 0, 0, 0x10]);
+        let mut group = GroupConfig::new(1);
+        group.members = vec![PhyPortId(0, 0), PhyPortId(1, 0)];
+        group.mac = Some(mac);
+        let config = LacConfig {
+            groups: vec![group],
+            ..Default::default()
+        };
+        lac_init_with_config(sim.clone(), Some(&config)).expect("Failed to init lac");
+
+        let card = sim.remove_chip(1).expect("Failed to remove chip");
+        let changes = lac_rescan().expect("Failed to rescan");
+        assert!(changes.contains(&TopologyChange::ChipRemoved(1)));
+        let current = lac_current_config().expect("Failed to capture config");
+        assert_eq!(current.groups[0].members, vec![PhyPortId(0, 0)]);
+
+        sim.add_chip(card).expect("Failed to add chip");
+        lac_rescan().expect("Failed to rescan");
+        let current = lac_current_config().expect("Failed to capture config");
+        assert_eq!(current.groups[0].members, config.groups[0].members);
+        assert_eq!(sim.mac(&PhyPortId(1, 0)).map(MacAddr::from), Some(mac));
+        lac_deinit().expect("Failed to deinit lac");
+    }
+}
@@ -0,0 +1,43 @@
+use lac::ffi::*;
+use lac::intf::*;
+use lac::lac::*;
+use lac::sdk::*;
+use std::thread;
+use std::time::Duration;
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_lac_init_keeps_link_changes_during_setup() {
+        let sim = SimSdk::with_topology(1, 2, 10000).expect("Failed to build simulator");
+        let sdk = FaultySdk::new(sim.clone());
+        sdk.set_latency(SdkOp::SetMac, Duration::from_millis(100));
+        let mut config = LacConfig::default();
+        let mut group = GroupConfig::new(1);
+        group.members = vec![PhyPortId(0, 0), PhyPortId(0, 1)];
+        group.mac = Some("02:00:00:00:00:01".parse().unwrap());
+        config.groups.push(group);
+
+        // The link comes up while the config is applied, after the groups
+        // took their link state from the topology.
+        let flap = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            sim.set_link_status(&PhyPortId(0, 0), LinkStatus::LINK_UP)
+                .expect("Failed to set link status");
+        });
+        lac_init_with_config(sdk, Some(&config)).expect("Failed to init lac");
+        flap.join().expect("Failed to join link thread");
+
+        let snapshot = lac_telemetry().expect("Failed to read telemetry");
+        let port = snapshot
+            .port(&PhyPortId(0, 0))
+            .expect("Missing port telemetry");
+        assert_eq!(port.status, LinkStatus::LINK_UP);
+        let group = snapshot.group(1).expect("Missing group telemetry");
+        assert_eq!(group.state, GroupState::Degraded);
+        assert_eq!(group.active_members, vec![PhyPortId(0, 0)]);
+    }
+}
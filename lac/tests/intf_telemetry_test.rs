@@ -0,0 +1,46 @@
+use lac::ffi::*;
+use lac::intf::*;
+use lac::lac::*;
+use lac::sdk::*;
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_lac_telemetry() {
+        assert_eq!(lac_telemetry(), Err(LacError::Uninitialized));
+
+        let sim = SimSdk::with_topology(1, 2, 10000).expect("Failed to build simulator");
+        let mut config = LacConfig::default();
+        let mut group = GroupConfig::new(1);
+        group.members = vec![PhyPortId(0, 0), PhyPortId(0, 1)];
+        config.groups.push(group);
+        lac_init_with_config(sim.clone(), Some(&config)).expect("Failed to init lac");
+
+        for status in [
+            LinkStatus::LINK_UP,
+            LinkStatus::LINK_DOWN,
+            LinkStatus::LINK_UP,
+        ] {
+            sim.set_link_status(&PhyPortId(0, 0), status)
+                .expect("Failed to set link status");
+        }
+        let snapshot = lac_telemetry().expect("Failed to read telemetry");
+        let port = snapshot
+            .port(&PhyPortId(0, 0))
+            .expect("Missing port telemetry");
+        assert_eq!(port.up_transitions, 2);
+        assert_eq!(port.down_transitions, 1);
+        assert_eq!(
+            snapshot.port(&PhyPortId(0, 1)).map(|port| port.flaps()),
+            Some(0)
+        );
+
+        let group = snapshot.group(1).expect("Missing group telemetry");
+        assert_eq!(group.state, GroupState::Degraded);
+        assert_eq!(group.active_members, vec![PhyPortId(0, 0)]);
+        assert_eq!(group.member_churn, 3);
+    }
+}
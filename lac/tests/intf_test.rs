@@ -0,0 +1,52 @@
+mod device;
+use device::*;
+use lac::ffi::*;
+use lac::intf::*;
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_query_chip_info() {
+        assert_eq!(lac_query_chip_info(), Err(LacError::Uninitialized));
+
+        let mut fixture = DeviceFixture::new();
+        for chip_id in 0..2 {
+            let mut chip = SwitchChip::new(chip_id);
+            for port_id in 0..2 {
+                chip.add_port(PhyPort {
+                    port_id,
+                    speed: 10000,
+                    status: LinkStatus::LINK_DOWN,
+                })
+                .expect("Failed to add port");
+            }
+            fixture.add_chip(chip).expect("Failed to add chip");
+        }
+
+        lac_init().expect("Failed to init lac");
+        fixture.activated = true;
+
+        let chips = lac_query_chip_info().expect("Failed to query chip info");
+        assert_eq!(chips.len(), 2);
+        assert_eq!(chips[1].chip_id, 1);
+        assert_eq!(
+            chips[1].ports[1],
+            PortInfo {
+                id: PhyPortId(1, 1),
+                speed: PortSpeed::GBPS_10,
+                status: LinkStatus::LINK_DOWN,
+                suppressed: false,
+            }
+        );
+
+        fixture
+            .set_link_status(&PhyPortId(1, 1), LinkStatus::LINK_UP)
+            .expect("Failed to set link status");
+        let chips = lac_query_chip_info().expect("Failed to query chip info");
+        assert_eq!(chips[1].ports[1].status, LinkStatus::LINK_UP);
+        assert_eq!(chips[0].ports[0].status, LinkStatus::LINK_DOWN);
+    }
+}
@@ -0,0 +1,80 @@
+use lac::ffi::*;
+use lac::intf::*;
+use lac::lac::*;
+use lac::sdk::SdkOp;
+use std::error::Error;
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_sdk_error_context() {
+        let err = Err::<(), _>(ChipSdkError::CHIP_SDK_BUSY)
+            .map_err(LacError::sdk(SdkOp::SetMac, Some(PhyPortId(1, 3))))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "SDK set_mac failed on port 1/3: Busy");
+        assert_eq!(err.code(), 107);
+        let source = err.source().expect("Missing source");
+        assert_eq!(
+            source.downcast_ref::<ChipSdkError>(),
+            Some(&ChipSdkError::CHIP_SDK_BUSY)
+        );
+        assert!(LacError::Uninitialized.source().is_none());
+
+        let err = LacError::device(SdkOp::Init, None)(DeviceError::Topology(
+            TopologyError::DuplicateChip(1),
+        ));
+        assert_eq!(err, LacError::Topology(TopologyError::DuplicateChip(1)));
+        assert_eq!(err.code(), 9);
+        assert!(err.to_string().starts_with("Invalid topology: "));
+    }
+
+    #[test]
+    fn test_from_lag_error() {
+        let mut lag = LagManager::new();
+        lag.create_group(1).expect("Failed to create group");
+        lag.create_group(2).expect("Failed to create group");
+        lag.add_member(1, PhyPortId(0, 0))
+            .expect("Failed to add member");
+
+        let err = LacError::from(lag.add_member(2, PhyPortId(0, 0)).unwrap_err());
+        assert_eq!(
+            err,
+            LacError::PortAlreadyAggregated {
+                port: PhyPortId(0, 0),
+                group: 1
+            }
+        );
+        assert_eq!(err.to_string(), "Port 0/0 already belongs to group 1");
+
+        let err = LacError::from(lag.remove_member(2, PhyPortId(0, 0)).unwrap_err());
+        assert_eq!(
+            err,
+            LacError::PortNotMember {
+                port: PhyPortId(0, 0),
+                group: 2
+            }
+        );
+        assert_eq!(err.code(), 7);
+        assert_eq!(
+            LacError::from(lag.delete_group(3).unwrap_err()),
+            LacError::GroupNotFound(3)
+        );
+        assert_eq!(
+            LacError::from(lag.set_min_links(1, 0).unwrap_err()),
+            LacError::InvalidMinLinks {
+                group: 1,
+                min_links: 0
+            }
+        );
+
+        let err = LacError::from(lag.create_group(1).unwrap_err());
+        assert_eq!(err.code(), 2);
+        assert_eq!(
+            err.to_string(),
+            "Invalid configuration: Group 1 already exists"
+        );
+    }
+}
@@ -1,6 +1,7 @@
 mod device;
 use device::*;
 use lac::ffi::*;
+use lac::lac::{DeviceError, MacAddr};
 
 #[cfg(test)]
 mod tests {
@@ -18,10 +19,8 @@ mod tests {
         let chip = fixture.get_chip(0).expect("Failed to get chip");
         assert_eq!(chip.chip_id, 0);
 
-        let mut device = Device::new();
-        fixture
-            .activate(&mut device)
-            .expect("Failed to setup device");
+        let device = Device::new();
+        fixture.activate(&device).expect("Failed to setup device");
 
         let phy_port = fixture
             .get_phy_port(&PhyPortId(0, 0))
@@ -33,12 +32,23 @@ mod tests {
             .expect("Failed to set link status");
         assert_eq!(phy_port.status, LinkStatus::LINK_UP);
 
+        let mac: MacAddr = "02:00:00:00:00:01".parse().unwrap();
         device
-            .set_mac(&PhyPortId(0, 0), &Mac { addr: [0; 6] })
+            .set_mac(&PhyPortId(0, 0), mac)
             .expect("Failed to set mac address");
-        let mac = fixture
+        let programmed = fixture
             .get_mac_addr(&PhyPortId(0, 0))
             .expect("Failed to get mac address");
-        assert_eq!(mac.addr, [0; 6]);
+        assert_eq!(MacAddr::from(*programmed), mac);
+
+        let multicast: MacAddr = "01:00:5e:00:00:01".parse().unwrap();
+        assert_eq!(
+            device.set_mac(&PhyPortId(0, 0), multicast),
+            Err(DeviceError::InvalidMac(multicast))
+        );
+        assert_eq!(
+            MacAddr::from(*fixture.get_mac_addr(&PhyPortId(0, 0)).unwrap()),
+            mac
+        );
     }
 }
@@ -0,0 +1,180 @@
+use lac::lac::{MacAddr, MacOwner, MacPool};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn config_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("lacctl-{}-{}.toml", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn lacctl(config: &Path, args: &[&str]) -> (bool, String, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_lacctl"))
+        .args(["--backend", "sim", "--topology", "2x2@25G", "--config"])
+        .arg(config)
+        .args(args)
+        .output()
+        .expect("Failed to run lacctl");
+    (
+        output.status.success(),
+        String::from_utf8(output.stdout).expect("Failed to decode stdout"),
+        String::from_utf8(output.stderr).expect("Failed to decode stderr"),
+    )
+}
+
+fn lacctl_json(config: &Path, args: &[&str]) -> Value {
+    let mut args = args.to_vec();
+    args.push("--json");
+    let (success, stdout, stderr) = lacctl(config, &args);
+    assert!(success, "lacctl {:?} failed: {}", args, stderr);
+    serde_json::from_str(&stdout).expect("Failed to parse lacctl output")
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_show_topology() {
+        let config = config_path("topology");
+        let chips = lacctl_json(&config, &["chips"]);
+        assert_eq!(chips.as_array().map(Vec::len), Some(2));
+        assert_eq!(chips[1]["chip"], 1);
+        assert_eq!(chips[1]["ports"], 2);
+
+        let ports = lacctl_json(&config, &["ports"]);
+        assert_eq!(ports.as_array().map(Vec::len), Some(4));
+        assert_eq!(ports[3]["port"], "1/1");
+        assert_eq!(ports[3]["speed"], "25G");
+        assert_eq!(ports[3]["link"], "down");
+
+        let (success, stdout, _) = lacctl(&config, &["chips"]);
+        assert!(success);
+        assert_eq!(stdout.lines().next(), Some("CHIP  PORTS  UP"));
+        assert!(!config.exists());
+    }
+
+    #[test]
+    fn test_groups_persist_across_runs() {
+        let config = config_path("groups");
+        let created = lacctl_json(&config, &["group", "create", "1", "--hash", "layer2+3"]);
+        assert_eq!(created["changes"][0], "create group 1");
+        lacctl_json(&config, &["member", "add", "1", "0/0"]);
+        lacctl_json(&config, &["member", "add", "1", "1/0"]);
+        lacctl_json(&config, &["group", "mac", "1", "02:00:00:00:00:10"]);
+
+        let groups = lacctl_json(&config, &["groups"]);
+        assert_eq!(groups[0]["group"], 1);
+        assert_eq!(groups[0]["hash"], "layer2+3");
+        assert_eq!(groups[0]["mac"], "02:00:00:00:00:10");
+        assert_eq!(groups[0]["members"][1]["port"], "1/0");
+
+        let macs = lacctl_json(&config, &["macs"]);
+        assert_eq!(macs.as_array().map(Vec::len), Some(2));
+        assert_eq!(macs[0]["group"], 1);
+
+        let removed = lacctl_json(&config, &["--dry-run", "member", "remove", "1", "0/0"]);
+        assert_eq!(removed["dry_run"], true);
+        assert_eq!(removed["changes"][0], "remove port 0/0 from group 1");
+        let groups = lacctl_json(&config, &["groups"]);
+        assert_eq!(groups[0]["members"].as_array().map(Vec::len), Some(2));
+
+        let (success, _, stderr) = lacctl(&config, &["member", "add", "1", "3/0"]);
+        assert!(!success);
+        assert!(stderr.contains("3/0"), "unexpected error: {}", stderr);
+        let (success, _, stderr) = lacctl(&config, &["mac", "set", "0/0", "02:00:00:00:00:11"]);
+        assert!(!success, "port MAC accepted on a MAC group member");
+        assert!(!stderr.is_empty());
+
+        std::fs::remove_file(&config).expect("Failed to remove config");
+    }
+
+    #[test]
+    fn test_group_create_takes_mac_from_pool() {
+        let config = config_path("mac-pool");
+        let pool_path = config.with_extension("pool.toml");
+        let base: MacAddr = "02:00:00:00:00:40".parse().unwrap();
+        MacPool::new(base, 4)
+            .expect("Failed to create pool")
+            .save(&pool_path)
+            .expect("Failed to save pool");
+        let pool_arg = pool_path.to_str().expect("Invalid pool path");
+
+        let dry_run = lacctl_json(
+            &config,
+            &["--dry-run", "group", "create", "1", "--mac-pool", pool_arg],
+        );
+        assert_eq!(dry_run["changes"][0], "create group 1");
+        let pool = MacPool::load(&pool_path).expect("Failed to load pool");
+        assert_eq!(pool.get(&MacOwner::Group(1)), None);
+
+        lacctl_json(&config, &["group", "create", "1", "--mac-pool", pool_arg]);
+        lacctl_json(&config, &["member", "add", "1", "0/0"]);
+        let groups = lacctl_json(&config, &["groups"]);
+        assert_eq!(groups[0]["mac"], "02:00:00:00:00:40");
+        let macs = lacctl_json(&config, &["macs"]);
+        assert_eq!(macs[0]["mac"], "02:00:00:00:00:40");
+        let pool = MacPool::load(&pool_path).expect("Failed to load pool");
+        assert_eq!(pool.get(&MacOwner::Group(1)), Some(base));
+
+        std::fs::remove_file(&config).expect("Failed to remove config");
+        std::fs::remove_file(&pool_path).expect("Failed to remove pool");
+    }
+
+    #[test]
+    fn test_dry_run_does_not_program_macs() {
+        let config = config_path("dry-run");
+        lacctl_json(&config, &["group", "create", "1"]);
+        lacctl_json(&config, &["member", "add", "1", "0/0"]);
+        lacctl_json(&config, &["group", "mac", "1", "02:00:00:00:00:10"]);
+
+        // Every set_mac fails, so the saved group MAC can only be loaded and
+        // the new member staged if the dry run never programs a MAC.
+        let added = lacctl_json(
+            &config,
+            &[
+                "--fail",
+                "set_mac",
+                "--dry-run",
+                "member",
+                "add",
+                "1",
+                "1/0",
+            ],
+        );
+        assert_eq!(added["changes"][0], "add port 1/0 to group 1");
+        assert_eq!(added["changes"][1], "set port 1/0 mac 02:00:00:00:00:10");
+        assert_eq!(added["changes"].as_array().map(Vec::len), Some(2));
+        let macs = lacctl_json(&config, &["--fail", "set_mac", "--dry-run", "macs"]);
+        assert_eq!(macs.as_array().map(Vec::len), Some(1));
+
+        let (success, _, stderr) =
+            lacctl(&config, &["--fail", "set_mac", "member", "add", "1", "1/0"]);
+        assert!(!success, "set_mac fault not injected");
+        assert!(
+            stderr.contains("rolled back"),
+            "unexpected error: {}",
+            stderr
+        );
+        let groups = lacctl_json(&config, &["groups"]);
+        assert_eq!(groups[0]["members"].as_array().map(Vec::len), Some(1));
+
+        std::fs::remove_file(&config).expect("Failed to remove config");
+    }
+
+    #[test]
+    fn test_events_rejects_invalid_timeout() {
+        let config = config_path("timeout");
+        for timeout in ["-1", "NaN", "inf", "1e300"] {
+            let (success, _, stderr) = lacctl(&config, &["events", "--timeout", timeout]);
+            assert!(!success, "timeout {} accepted", timeout);
+            assert!(
+                stderr.contains("Invalid timeout"),
+                "unexpected error: {}",
+                stderr
+            );
+        }
+    }
+}
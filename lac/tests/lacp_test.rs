@@ -0,0 +1,257 @@
+use lac::ffi::*;
+use lac::lac::lacp::*;
+use lac::lac::*;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::time::Duration;
+
+type Frames = Rc<RefCell<VecDeque<(PhyPortId, Lacpdu)>>>;
+
+#[derive(Default)]
+struct Endpoint {
+    inbox: Frames,
+    outbox: Frames,
+}
+
+impl FrameIo for Endpoint {
+    fn transmit(&mut self, port: PhyPortId, pdu: &Lacpdu) {
+        self.outbox.borrow_mut().push_back((port, *pdu));
+    }
+
+    fn receive(&mut self) -> Option<(PhyPortId, Lacpdu)> {
+        self.inbox.borrow_mut().pop_front()
+    }
+}
+
+struct Cable {
+    from: (usize, PhyPortId),
+    to: (usize, PhyPortId),
+}
+
+struct Network {
+    clock: ManualClock,
+    systems: Vec<Lacp<ManualClock, Endpoint>>,
+    cables: Vec<Cable>,
+}
+
+impl Network {
+    fn new(system_num: u8) -> Self {
+        let clock = ManualClock::new();
+        let systems = (0..system_num)
+            .map(|index| {
+                let system = SystemId {
+                    priority: 0x8000,
+                    mac: [0x02, 0, 0, 0, 0, index + 1],
+                };
+                Lacp::new(system, clock.clone(), Endpoint::default())
+            })
+            .collect();
+        Network {
+            clock,
+            systems,
+            cables: Vec::new(),
+        }
+    }
+
+    fn connect(&mut self, a: (usize, PhyPortId), b: (usize, PhyPortId), key: (u16, u16)) {
+        let config = |key| LacpPortConfig {
+            key,
+            timeout: LacpTimeout::Short,
+            ..Default::default()
+        };
+        self.systems[a.0]
+            .add_port(a.1, config(key.0))
+            .expect("Failed to add port");
+        self.systems[b.0]
+            .add_port(b.1, config(key.1))
+            .expect("Failed to add port");
+        self.systems[a.0].set_link_status(&a.1, LinkStatus::LINK_UP);
+        self.systems[b.0].set_link_status(&b.1, LinkStatus::LINK_UP);
+        self.cables.push(Cable { from: a, to: b });
+        self.cables.push(Cable { from: b, to: a });
+    }
+
+    fn step(&mut self) {
+        for system in self.systems.iter_mut() {
+            system.run();
+        }
+        for index in 0..self.systems.len() {
+            let frames: Vec<_> = self.systems[index]
+                .io()
+                .outbox
+                .borrow_mut()
+                .drain(..)
+                .collect();
+            for (port, pdu) in frames {
+                if let Some(cable) = self.cables.iter().find(|c| c.from == (index, port)) {
+                    let (peer, peer_port) = cable.to;
+                    self.systems[peer]
+                        .io()
+                        .inbox
+                        .borrow_mut()
+                        .push_back((peer_port, pdu));
+                }
+            }
+        }
+        self.clock.advance(Duration::from_millis(500));
+    }
+
+    fn run_for(&mut self, duration: Duration) {
+        for _ in 0..(duration.as_millis() / 500) {
+            self.step();
+        }
+    }
+
+    fn port(&self, system: usize, port: PhyPortId) -> &LacpPort {
+        self.systems[system].port(&port).expect("Unknown port")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_negotiated_ports_reach_distributing() {
+        let mut network = Network::new(2);
+        network.connect((0, PhyPortId(0, 0)), (1, PhyPortId(0, 0)), (1, 7));
+        network.connect((0, PhyPortId(0, 1)), (1, PhyPortId(1, 0)), (1, 7));
+        network.run_for(Duration::from_secs(10));
+
+        assert_eq!(
+            network.systems[0].distributing(1),
+            vec![PhyPortId(0, 0), PhyPortId(0, 1)]
+        );
+        assert_eq!(
+            network.systems[1].distributing(7),
+            vec![PhyPortId(0, 0), PhyPortId(1, 0)]
+        );
+
+        let port = network.port(0, PhyPortId(0, 0));
+        assert_eq!(port.rx_state(), RxState::Current);
+        assert_eq!(port.selected(), Selection::Selected);
+        assert_eq!(port.partner().key, 7);
+        assert!(port.partner().state.contains(LacpState::DISTRIBUTING));
+        assert!(!port.actor_churn() && !port.partner_churn());
+    }
+
+    #[test]
+    fn test_link_down_removes_port_from_distribution() {
+        let mut network = Network::new(2);
+        network.connect((0, PhyPortId(0, 0)), (1, PhyPortId(0, 0)), (1, 1));
+        network.connect((0, PhyPortId(0, 1)), (1, PhyPortId(0, 1)), (1, 1));
+        network.run_for(Duration::from_secs(10));
+
+        network.systems[0].set_link_status(&PhyPortId(0, 1), LinkStatus::LINK_DOWN);
+        network.run_for(Duration::from_secs(8));
+
+        assert_eq!(network.systems[0].distributing(1), vec![PhyPortId(0, 0)]);
+        assert_eq!(network.systems[1].distributing(1), vec![PhyPortId(0, 0)]);
+        assert_eq!(
+            network.port(0, PhyPortId(0, 1)).rx_state(),
+            RxState::PortDisabled
+        );
+        assert_eq!(
+            network.port(1, PhyPortId(0, 1)).rx_state(),
+            RxState::Defaulted
+        );
+    }
+
+    #[test]
+    fn test_ports_to_another_partner_stay_in_standby() {
+        let mut network = Network::new(3);
+        network.connect((0, PhyPortId(0, 0)), (1, PhyPortId(0, 0)), (1, 1));
+        network.connect((0, PhyPortId(0, 1)), (2, PhyPortId(0, 0)), (1, 1));
+        network.run_for(Duration::from_secs(10));
+
+        assert_eq!(network.systems[0].distributing(1), vec![PhyPortId(0, 0)]);
+        assert_eq!(
+            network.port(0, PhyPortId(0, 1)).selected(),
+            Selection::Standby
+        );
+        assert_eq!(
+            network.port(0, PhyPortId(0, 1)).mux_state(),
+            MuxState::Waiting
+        );
+        assert!(network.systems[2].distributing(1).is_empty());
+
+        network.run_for(Duration::from_secs(60));
+        assert!(network.port(0, PhyPortId(0, 1)).actor_churn());
+        assert!(network.port(2, PhyPortId(0, 0)).partner_churn());
+        assert!(!network.port(0, PhyPortId(0, 0)).actor_churn());
+    }
+
+    #[test]
+    fn test_silent_partner_defaults_and_churns() {
+        let mut network = Network::new(2);
+        network.connect((0, PhyPortId(0, 0)), (1, PhyPortId(0, 0)), (1, 1));
+        network.systems[1].remove_port(&PhyPortId(0, 0));
+        network.run_for(Duration::from_secs(5));
+
+        let port = network.port(0, PhyPortId(0, 0));
+        assert_eq!(port.rx_state(), RxState::Defaulted);
+        assert_eq!(port.mux_state(), MuxState::Attached);
+        assert!(port.actor().state.contains(LacpState::DEFAULTED));
+        assert!(!port.partner_churn());
+
+        network.run_for(Duration::from_secs(60));
+        let port = network.port(0, PhyPortId(0, 0));
+        assert!(port.partner_churn());
+        assert!(!port.actor_churn());
+    }
+
+    #[test]
+    fn test_port_numbers() {
+        let mut network = Network::new(1);
+        let system = &mut network.systems[0];
+        let last = CHIP_SDK_PHY_PORT_PER_CHIP as i32 - 1;
+        for (id, number) in [
+            (PhyPortId(0, 0), 1),
+            (PhyPortId(1, last), 2 * CHIP_SDK_PHY_PORT_PER_CHIP as u16),
+            (PhyPortId(8191, last - 1), u16::MAX),
+        ] {
+            system
+                .add_port(id, LacpPortConfig::default())
+                .expect("Failed to add port");
+            assert_eq!(system.port(&id).map(|port| port.actor().port), Some(number));
+        }
+        for id in [
+            PhyPortId(0, last + 1),
+            PhyPortId(-1, 0),
+            PhyPortId(0, -1),
+            PhyPortId(8191, last),
+        ] {
+            assert_eq!(
+                system.add_port(id, LacpPortConfig::default()),
+                Err(LacpError::InvalidPort(id))
+            );
+            assert!(system.port(&id).is_none());
+        }
+    }
+
+    #[test]
+    fn test_passive_ports_stay_silent() {
+        let mut network = Network::new(2);
+        for system in network.systems.iter_mut() {
+            system
+                .add_port(
+                    PhyPortId(0, 0),
+                    LacpPortConfig {
+                        key: 1,
+                        activity: LacpActivity::Passive,
+                        ..Default::default()
+                    },
+                )
+                .expect("Failed to add port");
+            system.set_link_status(&PhyPortId(0, 0), LinkStatus::LINK_UP);
+        }
+        network.run_for(Duration::from_secs(5));
+
+        let port = network.port(0, PhyPortId(0, 0));
+        assert_eq!(port.periodic_state(), PeriodicState::NoPeriodic);
+        assert!(network.systems[0].io().outbox.borrow().is_empty());
+        assert!(network.systems[0].distributing(1).is_empty());
+    }
+}
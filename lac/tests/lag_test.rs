@@ -0,0 +1,88 @@
+mod device;
+use device::*;
+use lac::ffi::*;
+use lac::lac::*;
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_group_lifecycle() {
+        let mut manager = LagManager::new();
+        manager.create_group(1).expect("Failed to create group");
+        manager.create_group(2).expect("Failed to create group");
+        assert_eq!(manager.create_group(1), Err(LagError::GroupExists(1)));
+
+        let ids: Vec<GroupId> = manager.groups().map(|group| group.id()).collect();
+        assert_eq!(ids, vec![1, 2]);
+
+        manager
+            .add_member(1, PhyPortId(0, 0))
+            .expect("Failed to add member");
+        let group = manager.delete_group(1).expect("Failed to delete group");
+        assert_eq!(group.members(), &[PhyPortId(0, 0)]);
+        assert_eq!(manager.group_of(&PhyPortId(0, 0)), None);
+        assert_eq!(
+            manager.delete_group(1).err(),
+            Some(LagError::GroupNotFound(1))
+        );
+    }
+
+    #[test]
+    fn test_group_membership_rules() {
+        let mut manager = LagManager::new();
+        manager.create_group(1).expect("Failed to create group");
+        manager.create_group(2).expect("Failed to create group");
+
+        for port in all_ports() {
+            manager.add_member(1, port).expect("Failed to add member");
+        }
+        assert_eq!(
+            manager.group(1).map(|group| group.members().len()),
+            Some(all_ports().count())
+        );
+        assert_eq!(
+            manager.add_member(2, PhyPortId(0, 0)),
+            Err(LagError::PortAlreadyAggregated(PhyPortId(0, 0), 1))
+        );
+        assert_eq!(
+            manager.remove_member(2, PhyPortId(0, 0)),
+            Err(LagError::PortNotMember(PhyPortId(0, 0), 2))
+        );
+
+        manager
+            .remove_member(1, PhyPortId(0, 0))
+            .expect("Failed to remove member");
+        manager
+            .add_member(2, PhyPortId(0, 0))
+            .expect("Failed to add member");
+        assert_eq!(manager.group_of(&PhyPortId(0, 0)), Some(2));
+    }
+
+    #[test]
+    fn test_group_state_follows_member_links() {
+        let mut manager = LagManager::new();
+        manager.create_group(1).expect("Failed to create group");
+        assert_eq!(manager.group_state(1), Ok(GroupState::Down));
+
+        manager
+            .add_member(1, PhyPortId(0, 0))
+            .expect("Failed to add member");
+        manager
+            .add_member(1, PhyPortId(1, 0))
+            .expect("Failed to add member");
+        assert_eq!(manager.group_state(1), Ok(GroupState::Down));
+
+        manager.update_link_status(PhyPortId(0, 0), LinkStatus::LINK_UP);
+        assert_eq!(manager.group_state(1), Ok(GroupState::Degraded));
+
+        manager.update_link_status(PhyPortId(1, 0), LinkStatus::LINK_UP);
+        assert_eq!(manager.group_state(1), Ok(GroupState::Up));
+
+        manager.update_link_status(PhyPortId(0, 0), LinkStatus::LINK_DOWN);
+        assert_eq!(manager.group_state(1), Ok(GroupState::Degraded));
+        assert_eq!(manager.group_state(3), Err(LagError::GroupNotFound(3)));
+    }
+}
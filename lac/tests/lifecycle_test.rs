@@ -0,0 +1,129 @@
+mod device;
+use device::*;
+use lac::ffi::*;
+use lac::lac::*;
+use lac::sdk::*;
+use std::sync::{Arc, Mutex};
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_deactivate_and_reactivate() {
+        let sim = SimSdk::with_topology(1, 2, 10000).expect("Failed to build simulator");
+        let device = Device::with_sdk(sim.clone());
+        assert_eq!(device.state(), DeviceState::Created);
+        assert_eq!(
+            device.set_mac(&PhyPortId(0, 0), mac(1)),
+            Err(DeviceError::InvalidState {
+                operation: "set a MAC",
+                state: DeviceState::Created,
+            })
+        );
+        assert!(device.link_events(4).is_err());
+
+        device.activate().expect("Failed to activate device");
+        assert_eq!(device.state(), DeviceState::Active);
+        assert!(matches!(
+            device.activate(),
+            Err(DeviceError::InvalidState {
+                state: DeviceState::Active,
+                ..
+            })
+        ));
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let events = Arc::clone(&received);
+        let _subscription = device
+            .subscribe_link_status(Box::new(move |port, status| {
+                events.lock().unwrap().push((port, status));
+            }))
+            .expect("Failed to subscribe");
+        device
+            .set_mac(&PhyPortId(0, 0), mac(1))
+            .expect("Failed to set mac");
+        sim.set_link_status(&PhyPortId(0, 1), LinkStatus::LINK_UP)
+            .unwrap();
+        assert!(device.port(&PhyPortId(0, 1)).unwrap().is_up());
+
+        device.deactivate().expect("Failed to deactivate device");
+        assert_eq!(device.state(), DeviceState::ShutDown);
+        assert_eq!(device.chips().count(), 0);
+        assert!(device.programmed_macs().is_empty());
+        sim.set_link_status(&PhyPortId(0, 1), LinkStatus::LINK_DOWN)
+            .unwrap();
+        assert_eq!(received.lock().unwrap().len(), 1);
+        assert!(matches!(
+            device.set_mac(&PhyPortId(0, 0), mac(2)),
+            Err(DeviceError::InvalidState {
+                state: DeviceState::ShutDown,
+                ..
+            })
+        ));
+        assert!(device.deactivate().is_err());
+
+        sim.add_chip(chip(1, &[0, 1, 2], 10000))
+            .expect("Failed to add chip");
+        device.activate().expect("Failed to reactivate device");
+        assert_eq!(device.state(), DeviceState::Active);
+        assert_eq!(device.chips().count(), 2);
+        assert_eq!(
+            device.port(&PhyPortId(1, 2)).map(|port| port.is_up()),
+            Some(false)
+        );
+
+        let events = device.link_events(4).expect("Failed to subscribe");
+        sim.set_link_status(&PhyPortId(1, 2), LinkStatus::LINK_UP)
+            .unwrap();
+        assert_eq!(
+            events.try_recv().map(|event| event.port),
+            Some(PhyPortId(1, 2))
+        );
+        assert_eq!(received.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_degraded_and_failed_activation() {
+        let sdk =
+            FaultySdk::new(SimSdk::with_topology(1, 2, 10000).expect("Failed to build simulator"));
+        sdk.inject(
+            Fault::new(SdkOp::Init, ChipSdkError::CHIP_SDK_ERROR).trigger(FaultTrigger::First(1)),
+        );
+        let device = Device::with_sdk(sdk.clone());
+        assert_eq!(
+            device.activate(),
+            Err(DeviceError::Sdk(ChipSdkError::CHIP_SDK_ERROR))
+        );
+        assert_eq!(device.state(), DeviceState::Created);
+        device.activate().expect("Failed to activate device");
+
+        sdk.inject(
+            Fault::new(SdkOp::SetMac, ChipSdkError::CHIP_SDK_BUSY).trigger(FaultTrigger::First(1)),
+        );
+        assert!(device.set_mac(&PhyPortId(0, 0), mac(1)).is_err());
+        assert_eq!(device.state(), DeviceState::Degraded);
+        device
+            .set_mac(&PhyPortId(0, 0), mac(1))
+            .expect("Failed to set mac");
+        assert_eq!(device.state(), DeviceState::Active);
+
+        sdk.inject(Fault::new(
+            SdkOp::SetMac,
+            ChipSdkError::CHIP_SDK_NO_RESOURCE,
+        ));
+        assert!(device.set_mac(&PhyPortId(0, 1), mac(2)).is_err());
+        assert_eq!(device.state(), DeviceState::Active);
+
+        let sim = SimSdk::new();
+        sim.add_chip(chip(0, &[0, 1], 10000)).unwrap();
+        sim.add_chip(chip(0, &[0, 1], 10000)).unwrap();
+        let device = Device::with_sdk(sim);
+        assert_eq!(
+            device.activate(),
+            Err(DeviceError::Topology(TopologyError::DuplicateChip(0)))
+        );
+        assert_eq!(device.state(), DeviceState::Created);
+    }
+}
@@ -0,0 +1,72 @@
+mod device;
+use device::*;
+use lac::ffi::*;
+use std::thread;
+use std::time::Duration;
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_link_events() {
+        let mut chip = SwitchChip::new(0);
+        chip.add_port(PhyPort::default())
+            .expect("Failed to add port");
+        let mut fixture = DeviceFixture::new();
+        fixture.add_chip(chip).expect("Failed to add chip");
+        let device = Device::new();
+        fixture.activate(&device).expect("Failed to setup device");
+
+        let events = device.link_events(2).expect("Failed to get link events");
+        let flap = |count: usize| {
+            for index in 0..count {
+                let status = if index % 2 == 0 {
+                    LinkStatus::LINK_UP
+                } else {
+                    LinkStatus::LINK_DOWN
+                };
+                fixture
+                    .set_link_status(&PhyPortId(0, 0), status)
+                    .expect("Failed to set link status");
+            }
+        };
+
+        flap(4);
+        let received: Vec<_> = events.try_iter().collect();
+        assert_eq!(received.len(), 2);
+        assert_eq!(received[0].port, PhyPortId(0, 0));
+        assert_eq!(received[0].status, LinkStatus::LINK_UP);
+        assert_eq!(received[1].status, LinkStatus::LINK_DOWN);
+        assert!(received[0].timestamp <= received[1].timestamp);
+        assert_eq!(received[0].missed, 0);
+        assert_eq!(events.missed(), 2);
+
+        flap(2);
+        let event = events.try_recv().expect("Missing link event");
+        assert_eq!(event.missed, 2);
+        assert_eq!(event.status, LinkStatus::LINK_UP);
+        assert_eq!(
+            events
+                .recv_timeout(Duration::from_millis(10))
+                .map(|event| event.missed),
+            Some(0)
+        );
+        assert_eq!(events.try_recv(), None);
+        drop(events);
+
+        let mut stream = device.link_events(4).expect("Failed to get link events");
+        let producer = thread::scope(|scope| {
+            scope.spawn(|| {
+                thread::sleep(Duration::from_millis(20));
+                flap(1);
+            });
+            next_event(&mut stream)
+        });
+        assert_eq!(
+            producer.map(|event| event.status),
+            Some(LinkStatus::LINK_UP)
+        );
+    }
+}
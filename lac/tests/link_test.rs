@@ -0,0 +1,60 @@
+mod device;
+use device::*;
+use lac::ffi::*;
+use std::sync::{Arc, Mutex};
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_link_status_subscriptions() {
+        let mut chip = SwitchChip::new(0);
+        for port_id in 0..2 {
+            chip.add_port(PhyPort {
+                port_id,
+                ..Default::default()
+            })
+            .expect("Failed to add port");
+        }
+        let mut fixture = DeviceFixture::new();
+        fixture.add_chip(chip).expect("Failed to add chip");
+        let device = Device::new();
+        fixture.activate(&device).expect("Failed to setup device");
+
+        let first = Arc::new(Mutex::new(Vec::new()));
+        let second = Arc::new(Mutex::new(Vec::new()));
+        let events = Arc::clone(&first);
+        let first_subscription = device
+            .subscribe_link_status(Box::new(move |port, status| {
+                events.lock().unwrap().push((port, status));
+            }))
+            .expect("Failed to subscribe");
+        let events = Arc::clone(&second);
+        let _second_subscription = device
+            .subscribe_link_status(Box::new(move |port, status| {
+                events.lock().unwrap().push((port, status));
+            }))
+            .expect("Failed to subscribe");
+
+        fixture
+            .set_link_status(&PhyPortId(0, 1), LinkStatus::LINK_UP)
+            .expect("Failed to set link status");
+        assert_eq!(
+            *first.lock().unwrap(),
+            vec![(PhyPortId(0, 1), LinkStatus::LINK_UP)]
+        );
+        assert_eq!(*second.lock().unwrap(), *first.lock().unwrap());
+
+        first_subscription.unsubscribe();
+        fixture
+            .set_link_status(&PhyPortId(0, 1), LinkStatus::LINK_DOWN)
+            .expect("Failed to set link status");
+        assert_eq!(first.lock().unwrap().len(), 1);
+        assert_eq!(
+            second.lock().unwrap().last(),
+            Some(&(PhyPortId(0, 1), LinkStatus::LINK_DOWN))
+        );
+    }
+}
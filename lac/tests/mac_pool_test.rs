@@ -0,0 +1,155 @@
+mod device;
+use device::*;
+use lac::ffi::*;
+use lac::lac::*;
+use std::collections::HashMap;
+
+fn base() -> MacAddr {
+    "02:00:00:00:10:00".parse().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_allocate_and_release() {
+        let mut pool = MacPool::new(base(), 3).expect("Failed to create pool");
+        let none = HashMap::new();
+        let port = MacOwner::Port(PhyPortId(0, 0));
+
+        let mac = pool.allocate(port, &none).expect("Failed to allocate");
+        assert_eq!(mac, base());
+        assert_eq!(pool.allocate(port, &none).ok(), Some(mac));
+
+        let programmed = HashMap::from([(PhyPortId(3, 0), base().checked_add(1).unwrap())]);
+        let group = pool
+            .allocate(MacOwner::Group(1), &programmed)
+            .expect("Failed to allocate");
+        assert_eq!(group, base().checked_add(2).unwrap());
+        assert!(matches!(
+            pool.allocate(MacOwner::Group(2), &programmed),
+            Err(MacPoolError::Exhausted)
+        ));
+
+        assert_eq!(pool.release(&port), Some(mac));
+        assert_eq!(pool.available(), 2);
+        assert_eq!(
+            pool.allocate(MacOwner::Group(2), &programmed).ok(),
+            Some(mac)
+        );
+
+        assert!(matches!(
+            MacPool::new("01:00:00:00:00:00".parse().unwrap(), 4),
+            Err(MacPoolError::InvalidRange(..))
+        ));
+        assert!(matches!(
+            MacPool::new(MacAddr::BROADCAST.checked_sub(1).unwrap(), 3),
+            Err(MacPoolError::InvalidRange(..))
+        ));
+        let top: MacAddr = "02:ff:ff:ff:ff:fe".parse().unwrap();
+        assert!(MacPool::new(top, 2).is_ok());
+        assert!(matches!(
+            MacPool::new(top, 3),
+            Err(MacPoolError::InvalidRange(..))
+        ));
+        assert!(matches!(
+            MacPool::new(base(), 0),
+            Err(MacPoolError::InvalidRange(..))
+        ));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let mut pool = MacPool::new(base(), 16).expect("Failed to create pool");
+        let none = HashMap::new();
+        pool.allocate(MacOwner::Port(PhyPortId(1, 2)), &none)
+            .expect("Failed to allocate");
+        pool.allocate(MacOwner::Group(7), &none)
+            .expect("Failed to allocate");
+
+        let saved = pool.to_toml().expect("Failed to save");
+        assert_eq!(
+            saved,
+            "base = \"02:00:00:00:10:00\"\nsize = 16\n\n\
+             [[port]]\nport = \"1/2\"\nmac = \"02:00:00:00:10:00\"\n\n\
+             [[group]]\nid = 7\nmac = \"02:00:00:00:10:01\"\n"
+        );
+        assert_eq!(MacPool::from_toml(&saved).ok(), Some(pool));
+
+        assert!(matches!(
+            MacPool::from_toml(
+                "base = \"02:00:00:00:10:00\"\nsize = 16\n\
+                 [[port]]\nport = \"1-2\"\nmac = \"02:00:00:00:10:00\"\n"
+            ),
+            Err(MacPoolError::Parse(_))
+        ));
+        assert!(matches!(
+            MacPool::from_toml(
+                "base = \"02:00:00:00:10:00\"\nsize = 16\n\
+                 [[group]]\nid = 1\nmac = \"02:00:00:00:10:00\"\n\
+                 [[group]]\nid = 2\nmac = \"02:00:00:00:10:00\"\n"
+            ),
+            Err(MacPoolError::InUse(_, MacOwner::Group(1)))
+        ));
+        assert!(matches!(
+            MacPool::from_toml("base = \"01:00:00:00:10:00\"\nsize = 16\n"),
+            Err(MacPoolError::InvalidRange(..))
+        ));
+    }
+
+    #[test]
+    fn test_group_mac_on_device() {
+        let mut chip = SwitchChip::new(0);
+        for port_id in 0..3 {
+            chip.add_port(PhyPort {
+                port_id,
+                ..Default::default()
+            })
+            .expect("Failed to add port");
+        }
+        let mut fixture = DeviceFixture::new();
+        fixture.add_chip(chip).expect("Failed to add chip");
+        let device = Device::new();
+        fixture.activate(&device).expect("Failed to setup device");
+
+        device
+            .set_mac(&PhyPortId(0, 2), base())
+            .expect("Failed to set mac");
+
+        let mut pool = MacPool::new(base(), 8).expect("Failed to create pool");
+        let mut lag = LagManager::new();
+        lag.create_group(1).expect("Failed to create group");
+        lag.add_member(1, PhyPortId(0, 0))
+            .expect("Failed to add member");
+        lag.add_member(1, PhyPortId(0, 1))
+            .expect("Failed to add member");
+
+        let mac = apply_group_mac(&device, &mut pool, lag.group_mut(1).expect("Missing group"))
+            .expect("Failed to apply");
+        assert_eq!(mac, base().checked_add(1).unwrap());
+        assert_eq!(lag.group(1).and_then(|group| group.mac()), Some(mac));
+        for port_id in 0..2 {
+            let programmed = fixture
+                .get_mac_addr(&PhyPortId(0, port_id))
+                .expect("Failed to get mac address");
+            assert_eq!(MacAddr::from(*programmed), mac);
+        }
+        assert!(pool
+            .collisions(&device.programmed_macs(), |port| lag.group_of(port))
+            .is_empty());
+
+        device
+            .set_mac(&PhyPortId(0, 2), mac)
+            .expect("Failed to set mac");
+        assert_eq!(
+            pool.collisions(&device.programmed_macs(), |port| lag.group_of(port)),
+            vec![MacCollision {
+                mac,
+                owner: MacOwner::Group(1),
+                port: PhyPortId(0, 2),
+            }]
+        );
+    }
+}
@@ -0,0 +1,72 @@
+use lac::ffi::*;
+use lac::lac::MacAddr;
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_parse_and_format() {
+        let expected = MacAddr::new([0xaa, 0xbb, 0xcc, 0x0d, 0xee, 0xff]);
+        for text in ["aa:bb:cc:0d:ee:ff", "AA-BB-CC-0D-EE-FF", "aabb.cc0d.eeff"] {
+            assert_eq!(text.parse::<MacAddr>(), Ok(expected), "{}", text);
+        }
+        assert_eq!(expected.to_string(), "aa:bb:cc:0d:ee:ff");
+
+        for text in [
+            "",
+            "aa:bb:cc:dd:ee",
+            "aa:bb:cc:dd:ee:ff:00",
+            "aa-bb:cc-dd:ee-ff",
+            "aabb.ccdd",
+            "aab.bccdd.eeff",
+            "gg:bb:cc:dd:ee:ff",
+            "+a:bb:cc:dd:ee:ff",
+        ] {
+            assert!(text.parse::<MacAddr>().is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_address_kinds() {
+        let unicast: MacAddr = "00:1b:21:00:00:01".parse().unwrap();
+        assert!(unicast.is_unicast() && unicast.is_universal());
+        assert!(!unicast.is_multicast() && !unicast.is_locally_administered());
+
+        let local: MacAddr = "02:00:00:00:00:01".parse().unwrap();
+        assert!(local.is_unicast() && local.is_locally_administered());
+
+        let multicast: MacAddr = "01:80:c2:00:00:02".parse().unwrap();
+        assert!(multicast.is_multicast() && !multicast.is_broadcast());
+
+        assert!(MacAddr::BROADCAST.is_multicast() && MacAddr::BROADCAST.is_broadcast());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let base: MacAddr = "02:00:00:00:00:fe".parse().unwrap();
+        let port_mac = base.checked_add(3).unwrap();
+        assert_eq!(port_mac.to_string(), "02:00:00:00:01:01");
+        assert_eq!(port_mac.offset_from(&base), Some(3));
+        assert_eq!(base.offset_from(&port_mac), None);
+        assert_eq!(port_mac.checked_sub(3), Some(base));
+        assert_eq!(MacAddr::BROADCAST.checked_add(1), None);
+        assert_eq!(MacAddr::ZERO.checked_sub(1), None);
+        assert_eq!(
+            MacAddr::from_u64(0x0200_0000_0001),
+            "02:00:00:00:00:01".parse().ok()
+        );
+        assert_eq!(MacAddr::from_u64(1 << 48), None);
+    }
+
+    #[test]
+    fn test_ffi_conversion() {
+        let mac = Mac {
+            addr: [0x02, 0x11, 0x22, 0x33, 0x44, 0x55],
+        };
+        let addr = MacAddr::from(mac);
+        assert_eq!(addr.octets(), mac.addr);
+        assert_eq!(Mac::from(addr).addr, mac.addr);
+    }
+}
@@ -0,0 +1,126 @@
+mod device;
+use device::*;
+use lac::ffi::*;
+use lac::lac::*;
+use std::collections::HashMap;
+
+const A: PhyPortId = PhyPortId(0, 0);
+const B: PhyPortId = PhyPortId(0, 1);
+const C: PhyPortId = PhyPortId(1, 0);
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_group_and_member_events() {
+        let mut lag = group(&[A, B], PortSpeed::GBPS_10, LinkStatus::LINK_DOWN);
+        lag.set_min_links(1, 2).expect("Failed to set min links");
+        let macs = HashMap::new();
+        let mut observer = LagObserver::new();
+        assert_eq!(observer.observe(&lag, &macs, None), Vec::new());
+
+        lag.update_link_status(A, LinkStatus::LINK_UP);
+        assert_eq!(
+            observer.observe(&lag, &macs, Some(A)),
+            vec![LagEvent::MemberSelected { group: 1, port: A }]
+        );
+
+        lag.update_link_status(B, LinkStatus::LINK_UP);
+        let events = observer.observe(&lag, &macs, Some(B));
+        assert_eq!(
+            events,
+            vec![
+                LagEvent::MemberSelected { group: 1, port: B },
+                LagEvent::GroupUp {
+                    group: 1,
+                    port: Some(B),
+                },
+                LagEvent::MemberDistributing { group: 1, port: A },
+                LagEvent::MemberDistributing { group: 1, port: B },
+            ]
+        );
+        assert!(events.iter().all(|event| event.group() == 1));
+        assert_eq!(events[2].port(), Some(A));
+        assert_eq!(events[1].to_string(), "group 1 up");
+        assert_eq!(observer.observe(&lag, &macs, None), Vec::new());
+
+        lag.update_link_status(A, LinkStatus::LINK_DOWN);
+        assert_eq!(
+            observer.observe(&lag, &macs, Some(A)),
+            vec![
+                LagEvent::MemberDown { group: 1, port: A },
+                LagEvent::MemberSelected { group: 1, port: B },
+                LagEvent::GroupDown {
+                    group: 1,
+                    port: Some(A),
+                },
+            ]
+        );
+
+        lag.delete_group(1).expect("Failed to delete group");
+        assert_eq!(
+            observer.observe(&lag, &macs, None),
+            vec![LagEvent::MemberDown { group: 1, port: B }]
+        );
+    }
+
+    #[test]
+    fn test_standby_and_mac_events() {
+        let members: Vec<_> = (0..=CHIP_SDK_PHY_PORT_PER_GROUP_MAX as i32)
+            .map(|port_id| PhyPortId(0, port_id))
+            .collect();
+        let mut lag = group(&members, PortSpeed::GBPS_10, LinkStatus::LINK_DOWN);
+        let last = *members.last().expect("No members");
+        let mut observer = LagObserver::new();
+        for port in &members {
+            lag.update_link_status(*port, LinkStatus::LINK_UP);
+        }
+        let events = observer.observe(&lag, &HashMap::new(), None);
+        assert!(events.contains(&LagEvent::MemberStandby {
+            group: 1,
+            port: last,
+        }));
+        assert!(!events.contains(&LagEvent::MemberSelected {
+            group: 1,
+            port: last,
+        }));
+
+        let mac = MacAddr::new([0x02, 0, 0, 0, 0, 1]);
+        lag.set_group_mac(1, Some(mac))
+            .expect("Failed to set group MAC");
+        let mut macs: HashMap<_, _> = members.iter().map(|port| (*port, mac)).collect();
+        macs.insert(C, mac);
+        let events = observer.observe(&lag, &macs, None);
+        assert_eq!(events.len(), members.len());
+        assert_eq!(
+            events[0],
+            LagEvent::MacProgrammed {
+                group: 1,
+                port: members[0],
+                mac,
+            }
+        );
+        assert_eq!(observer.observe(&lag, &macs, None), Vec::new());
+
+        lag.update_link_status(members[0], LinkStatus::LINK_DOWN);
+        assert_eq!(
+            observer.observe(&lag, &macs, Some(members[0])),
+            vec![
+                LagEvent::MemberDown {
+                    group: 1,
+                    port: members[0],
+                },
+                LagEvent::MemberSelected {
+                    group: 1,
+                    port: last,
+                },
+                LagEvent::MemberDistributing {
+                    group: 1,
+                    port: last,
+                },
+            ]
+        );
+    }
+}
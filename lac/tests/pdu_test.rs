@@ -0,0 +1,185 @@
+use lac::lac::lacp::pdu::*;
+use lac::lac::lacp::*;
+use proptest::prelude::*;
+
+const SRC: [u8; 6] = [0x02, 0x00, 0x00, 0x00, 0x00, 0x01];
+
+fn lacp_frame() -> SlowFrame {
+    SlowFrame {
+        src: SRC,
+        pdu: SlowPdu::Lacp(Lacpdu {
+            actor: LacpInfo {
+                system: SystemId {
+                    priority: 0x8000,
+                    mac: SRC,
+                },
+                key: 0x0001,
+                port_priority: 0x00ff,
+                port: 0x0002,
+                state: LacpState::from_bits(0x3d),
+            },
+            partner: LacpInfo {
+                system: SystemId {
+                    priority: 0x0100,
+                    mac: [0x02, 0, 0, 0, 0, 0x02],
+                },
+                key: 0x0007,
+                port_priority: 0x8000,
+                port: 0x0010,
+                state: LacpState::from_bits(0x05),
+            },
+            collector_max_delay: 0x1234,
+        }),
+    }
+}
+
+prop_compose! {
+    fn arb_info()(
+        priority in any::<u16>(),
+        mac in any::<[u8; 6]>(),
+        key in any::<u16>(),
+        port_priority in any::<u16>(),
+        port in any::<u16>(),
+        state in any::<u8>(),
+    ) -> LacpInfo {
+        LacpInfo {
+            system: SystemId { priority, mac },
+            key,
+            port_priority,
+            port,
+            state: LacpState::from_bits(state),
+        }
+    }
+}
+
+fn arb_pdu() -> impl Strategy<Value = SlowPdu> {
+    prop_oneof![
+        (arb_info(), arb_info(), any::<u16>()).prop_map(|(actor, partner, delay)| {
+            SlowPdu::Lacp(Lacpdu {
+                actor,
+                partner,
+                collector_max_delay: delay,
+            })
+        }),
+        (any::<bool>(), any::<u16>(), any::<[u8; 6]>(), any::<u32>()).prop_map(
+            |(response, port, system, id)| {
+                SlowPdu::Marker(MarkerPdu {
+                    kind: if response {
+                        MarkerKind::Response
+                    } else {
+                        MarkerKind::Request
+                    },
+                    requester_port: port,
+                    requester_system: system,
+                    transaction_id: id,
+                })
+            }
+        ),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn test_round_trip(src in any::<[u8; 6]>(), pdu in arb_pdu()) {
+        let frame = SlowFrame { src, pdu };
+        let bytes = frame.encode();
+        prop_assert_eq!(bytes.len(), FRAME_LEN);
+        prop_assert_eq!(SlowFrame::decode(&bytes), Ok(frame));
+    }
+
+    #[test]
+    fn test_decode_never_panics(bytes in proptest::collection::vec(any::<u8>(), 0..160)) {
+        let _ = SlowFrame::decode(&bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_lacpdu_layout() {
+        let bytes = lacp_frame().encode();
+        assert_eq!(&bytes[0..6], &SLOW_PROTOCOLS_MAC);
+        assert_eq!(&bytes[6..12], &SRC);
+        assert_eq!(&bytes[12..16], &[0x88, 0x09, 0x01, 0x01]);
+        assert_eq!(
+            &bytes[16..38],
+            &[
+                0x01, 0x14, 0x80, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x01, 0x00, 0xff,
+                0x00, 0x02, 0x3d, 0x00, 0x00, 0x00, 0x02, 0x14
+            ]
+        );
+        assert_eq!(&bytes[56..60], &[0x03, 0x10, 0x12, 0x34]);
+        assert!(bytes[60..].iter().all(|byte| *byte == 0));
+    }
+
+    #[test]
+    fn test_marker_layout() {
+        let frame = SlowFrame {
+            src: SRC,
+            pdu: SlowPdu::Marker(MarkerPdu {
+                kind: MarkerKind::Response,
+                requester_port: 3,
+                requester_system: SRC,
+                transaction_id: 0xdeadbeef,
+            }),
+        };
+        let bytes = frame.encode();
+        assert_eq!(
+            &bytes[14..32],
+            &[
+                0x02, 0x01, 0x02, 0x10, 0x00, 0x03, 0x02, 0x00, 0x00, 0x00, 0x00, 0x01, 0xde, 0xad,
+                0xbe, 0xef, 0x00, 0x00
+            ]
+        );
+        assert_eq!(&bytes[32..34], &[0x00, 0x00]);
+    }
+
+    #[test]
+    fn test_decode_rejects_malformed_frames() {
+        let bytes = lacp_frame().encode();
+        assert_eq!(
+            SlowFrame::decode(&bytes[..60]),
+            Err(PduError::Truncated {
+                expected: FRAME_LEN,
+                actual: 60
+            })
+        );
+
+        let corrupt = |offset: usize, value: u8| {
+            let mut bytes = bytes.clone();
+            bytes[offset] = value;
+            SlowFrame::decode(&bytes)
+        };
+        assert!(matches!(
+            corrupt(5, 0x03),
+            Err(PduError::InvalidDestination(_))
+        ));
+        assert_eq!(corrupt(13, 0x00), Err(PduError::InvalidEtherType(0x8800)));
+        assert_eq!(corrupt(14, 0x0a), Err(PduError::UnsupportedSubtype(0x0a)));
+        assert_eq!(corrupt(15, 0x02), Err(PduError::UnsupportedVersion(2)));
+        assert_eq!(
+            corrupt(36, 0x03),
+            Err(PduError::UnexpectedTlv {
+                expected: 0x02,
+                actual: 0x03
+            })
+        );
+        assert_eq!(
+            corrupt(17, 0x13),
+            Err(PduError::InvalidTlvLength {
+                tlv: 0x01,
+                length: 0x13
+            })
+        );
+        assert_eq!(
+            corrupt(72, 0x01),
+            Err(PduError::UnexpectedTlv {
+                expected: 0x00,
+                actual: 0x01
+            })
+        );
+    }
+}
@@ -0,0 +1,112 @@
+mod device;
+use device::*;
+use lac::ffi::*;
+use lac::lac::*;
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_rescan_reports_changes() {
+        let (sim, device) = activated(2, 2, 10000);
+        assert_eq!(device.rescan(), Ok(Vec::new()));
+
+        let mac = MacAddr::new([0x02, 0, 0, 0, 0, 1]);
+        device
+            .set_mac(&PhyPortId(1, 0), mac)
+            .expect("Failed to set MAC");
+        device
+            .set_mac(&PhyPortId(0, 0), mac)
+            .expect("Failed to set MAC");
+        sim.remove_chip(1).expect("Failed to remove chip");
+        sim.set_port_speed(&PhyPortId(0, 1), 25000)
+            .expect("Failed to set port speed");
+        sim.add_chip(chip(2, &[0], 10000))
+            .expect("Failed to add chip");
+
+        let changes = device.rescan().expect("Failed to rescan");
+        assert_eq!(
+            changes,
+            vec![
+                TopologyChange::SpeedChanged {
+                    port: PhyPortId(0, 1),
+                    from: PortSpeed::GBPS_10,
+                    to: PortSpeed::GBPS_25,
+                },
+                TopologyChange::PortRemoved(PhyPortId(1, 0)),
+                TopologyChange::PortRemoved(PhyPortId(1, 1)),
+                TopologyChange::ChipRemoved(1),
+                TopologyChange::ChipAdded(2),
+                TopologyChange::PortAdded(PhyPortId(2, 0)),
+            ]
+        );
+        assert!(device.port(&PhyPortId(1, 0)).is_none());
+        assert_eq!(
+            device.port(&PhyPortId(0, 1)).map(|port| port.speed),
+            Some(PortSpeed::GBPS_25)
+        );
+        assert_eq!(device.mac(&PhyPortId(1, 0)), None);
+        assert_eq!(device.mac(&PhyPortId(0, 0)), Some(mac));
+        assert!(device.damping_status(&PhyPortId(1, 0)).is_none());
+        assert!(device.damping_status(&PhyPortId(2, 0)).is_some());
+        assert_eq!(
+            TopologyChange::PortAdded(PhyPortId(2, 0)).to_string(),
+            "port 2/0 added"
+        );
+
+        device.deactivate().expect("Failed to deactivate device");
+        assert_eq!(
+            device.rescan(),
+            Err(DeviceError::InvalidState {
+                operation: "rescan",
+                state: DeviceState::ShutDown,
+            })
+        );
+    }
+
+    #[test]
+    fn test_line_card_swap_restores_membership() {
+        let (sim, device) = activated(2, 2, 10000);
+        let mut lag = LagManager::new();
+        lag.sync_topology(&device.topology());
+        lag.create_group(1).expect("Failed to create group");
+        lag.add_member(1, PhyPortId(0, 0))
+            .expect("Failed to add member");
+        lag.add_member(1, PhyPortId(1, 0))
+            .expect("Failed to add member");
+        lag.add_member(1, PhyPortId(1, 1))
+            .expect("Failed to add member");
+
+        let card = sim.remove_chip(1).expect("Failed to remove chip");
+        let changes = device.rescan().expect("Failed to rescan");
+        assert_eq!(
+            lag.apply_topology_changes(&changes),
+            vec![
+                MembershipChange::Detached(1, PhyPortId(1, 0)),
+                MembershipChange::Detached(1, PhyPortId(1, 1)),
+            ]
+        );
+        assert_eq!(lag.group(1).map(|group| group.members().len()), Some(1));
+        assert_eq!(lag.detached_members().count(), 2);
+
+        // The port coming back takes another slot in the meantime.
+        lag.create_group(2).expect("Failed to create group");
+        lag.add_member(2, PhyPortId(1, 1))
+            .expect("Failed to add member");
+
+        sim.add_chip(card).expect("Failed to add chip");
+        let changes = device.rescan().expect("Failed to rescan");
+        assert_eq!(
+            lag.apply_topology_changes(&changes),
+            vec![MembershipChange::Restored(1, PhyPortId(1, 0))]
+        );
+        assert_eq!(
+            lag.group(1).map(|group| group.members().to_vec()),
+            Some(vec![PhyPortId(0, 0), PhyPortId(1, 0)])
+        );
+        assert_eq!(lag.group_of(&PhyPortId(1, 1)), Some(2));
+        assert_eq!(lag.detached_members().count(), 0);
+    }
+}
@@ -0,0 +1,197 @@
+mod device;
+use device::*;
+use lac::ffi::*;
+use lac::lac::*;
+use lac::sdk::*;
+use std::thread;
+use std::time::Duration;
+
+fn policy() -> RetryPolicy {
+    RetryPolicy {
+        max_attempts: 4,
+        initial_backoff: Duration::from_millis(1),
+        max_backoff: Duration::from_millis(3),
+        multiplier: 2,
+        jitter: 0.0,
+        deadline: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let policy = policy();
+        let backoffs: Vec<_> = (1..=4).map(|retry| policy.backoff(retry)).collect();
+        assert_eq!(backoffs, [1, 2, 3, 3].map(Duration::from_millis).to_vec());
+        assert!(ChipSdkError::CHIP_SDK_BUSY.is_transient());
+        assert!(ChipSdkError::CHIP_SDK_TIMEOUT.is_transient());
+        assert!(!ChipSdkError::CHIP_SDK_NO_RESOURCE.is_transient());
+
+        let (result, attempts) =
+            RetryPolicy::never().run(SdkOp::Init, || Err(ChipSdkError::CHIP_SDK_BUSY));
+        assert_eq!(result, Err(ChipSdkError::CHIP_SDK_BUSY));
+        assert_eq!(attempts.len(), 1);
+        assert_eq!(attempts[0].backoff, None);
+    }
+
+    #[test]
+    fn test_set_mac_survives_contention() {
+        let sdk =
+            FaultySdk::new(SimSdk::with_topology(1, 1, 10000).expect("Failed to build simulator"));
+        let device = activated_on(&sdk);
+        device.set_retry_policy(policy());
+        sdk.inject(
+            Fault::new(SdkOp::SetMac, ChipSdkError::CHIP_SDK_BUSY).trigger(FaultTrigger::First(2)),
+        );
+        let mac = MacAddr::new([0x02, 0, 0, 0, 0, 1]);
+
+        device
+            .set_mac(&PhyPortId(0, 0), mac)
+            .expect("Failed to set mac");
+        let attempts = device.last_attempts();
+        let results: Vec<_> = attempts.iter().map(|attempt| attempt.result).collect();
+        assert_eq!(
+            results,
+            vec![
+                Err(ChipSdkError::CHIP_SDK_BUSY),
+                Err(ChipSdkError::CHIP_SDK_BUSY),
+                Ok(())
+            ]
+        );
+        let backoffs: Vec<_> = attempts.iter().map(|attempt| attempt.backoff).collect();
+        assert_eq!(
+            backoffs,
+            vec![
+                Some(Duration::from_millis(1)),
+                Some(Duration::from_millis(2)),
+                None
+            ]
+        );
+        assert!(attempts.iter().all(|attempt| attempt.op == SdkOp::SetMac));
+        assert_eq!(device.mac(&PhyPortId(0, 0)), Some(mac));
+    }
+
+    #[test]
+    fn test_give_up() {
+        let sdk =
+            FaultySdk::new(SimSdk::with_topology(1, 2, 10000).expect("Failed to build simulator"));
+        let device = activated_on(&sdk);
+        device.set_retry_policy(policy());
+        let mac = MacAddr::new([0x02, 0, 0, 0, 0, 1]);
+
+        sdk.inject(
+            Fault::new(SdkOp::SetMac, ChipSdkError::CHIP_SDK_NO_RESOURCE).on_port(PhyPortId(0, 0)),
+        );
+        assert_eq!(
+            device.set_mac(&PhyPortId(0, 0), mac),
+            Err(DeviceError::Sdk(ChipSdkError::CHIP_SDK_NO_RESOURCE))
+        );
+        assert_eq!(device.last_attempts().len(), 1);
+
+        sdk.inject(
+            Fault::new(SdkOp::SetMac, ChipSdkError::CHIP_SDK_TIMEOUT).on_port(PhyPortId(0, 1)),
+        );
+        assert_eq!(
+            device.set_mac(&PhyPortId(0, 1), mac),
+            Err(DeviceError::Sdk(ChipSdkError::CHIP_SDK_TIMEOUT))
+        );
+        assert_eq!(device.last_attempts().len(), 4);
+
+        device.set_retry_policy(RetryPolicy {
+            initial_backoff: Duration::from_millis(50),
+            max_backoff: Duration::from_millis(50),
+            deadline: Some(Duration::from_millis(20)),
+            ..policy()
+        });
+        assert_eq!(
+            device.set_mac(&PhyPortId(0, 1), mac),
+            Err(DeviceError::Sdk(ChipSdkError::CHIP_SDK_TIMEOUT))
+        );
+        assert_eq!(device.last_attempts().len(), 1);
+        assert_eq!(sdk.calls(SdkOp::SetMac), 6);
+    }
+
+    #[test]
+    fn test_backoff_releases_sdk() {
+        let sdk =
+            FaultySdk::new(SimSdk::with_topology(1, 2, 10000).expect("Failed to build simulator"));
+        let device = activated_on(&sdk);
+        device.set_retry_policy(policy());
+        device.set_retry_policy(RetryPolicy {
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_millis(500),
+            ..policy()
+        });
+        sdk.inject(
+            Fault::new(SdkOp::SetMac, ChipSdkError::CHIP_SDK_BUSY)
+                .on_port(PhyPortId(0, 0))
+                .trigger(FaultTrigger::First(1)),
+        );
+        let mac = MacAddr::new([0x02, 0, 0, 0, 0, 1]);
+
+        let retrying = thread::spawn({
+            let device = device.clone();
+            move || device.set_mac(&PhyPortId(0, 0), mac)
+        });
+        while sdk.calls(SdkOp::SetMac) == 0 {
+            thread::yield_now();
+        }
+        device
+            .set_mac(&PhyPortId(0, 1), mac)
+            .expect("Failed to set mac");
+        assert!(!retrying.is_finished());
+        retrying
+            .join()
+            .unwrap()
+            .expect("Failed to set mac after backoff");
+        assert_eq!(device.mac(&PhyPortId(0, 0)), Some(mac));
+        assert_eq!(sdk.calls(SdkOp::SetMac), 3);
+    }
+
+    #[test]
+    fn test_jitter_bounds() {
+        let policy = RetryPolicy {
+            jitter: 0.5,
+            ..policy()
+        };
+        let sdk =
+            FaultySdk::new(SimSdk::with_topology(1, 1, 10000).expect("Failed to build simulator"));
+        sdk.inject(Fault::new(SdkOp::Init, ChipSdkError::CHIP_SDK_BUSY));
+        let device = Device::with_sdk(sdk);
+        device.set_retry_policy(policy);
+
+        for _ in 0..10 {
+            assert!(device.activate().is_err());
+            let attempts = device.last_attempts();
+            assert_eq!(attempts.len(), 4);
+            for attempt in &attempts[..3] {
+                let nominal = policy.backoff(attempt.number);
+                let backoff = attempt.backoff.expect("Missing backoff");
+                assert!(backoff >= nominal / 2 && backoff <= nominal * 3 / 2);
+                assert!(backoff <= policy.max_backoff);
+            }
+        }
+    }
+
+    #[test]
+    fn test_jitter_saturates_at_max_backoff() {
+        let policy = RetryPolicy {
+            max_attempts: 2,
+            initial_backoff: Duration::MAX,
+            max_backoff: Duration::MAX,
+            jitter: 1.0,
+            deadline: Some(Duration::ZERO),
+            ..policy()
+        };
+        for _ in 0..10 {
+            let (result, attempts) = policy.run(SdkOp::SetMac, || Err(ChipSdkError::CHIP_SDK_BUSY));
+            assert_eq!(result, Err(ChipSdkError::CHIP_SDK_BUSY));
+            assert_eq!(attempts.len(), 1);
+            assert_eq!(attempts[0].backoff, None);
+        }
+    }
+}
@@ -0,0 +1,198 @@
+mod device;
+use device::*;
+use lac::ffi::*;
+use lac::lac::*;
+use lac::sdk::*;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_activate_topology() {
+        let (_, device) = activated(2, 3, 10000);
+        assert_eq!(device.chips().count(), 2);
+        for (chip_id, chip) in device.chips().enumerate() {
+            assert_eq!(chip.id(), chip_id as ChipId);
+            assert_eq!(chip.ports().count(), 3);
+            assert!(chip
+                .ports()
+                .all(|port| port.speed == PortSpeed::GBPS_10 && !port.is_up()));
+        }
+
+        let sim = SimSdk::new();
+        for chip_id in 0..CHIP_SDK_CHIP_MAX {
+            sim.add_chip(SwitchChip {
+                chip_id: chip_id as ChipId,
+                ..Default::default()
+            })
+            .expect("Failed to add chip");
+        }
+        assert_eq!(
+            sim.add_chip(SwitchChip::default()),
+            Err(ChipSdkError::CHIP_SDK_NO_RESOURCE)
+        );
+
+        assert!(matches!(
+            SimSdk::with_topology(1, CHIP_SDK_PHY_PORT_PER_CHIP + 1, 10000),
+            Err(ChipSdkError::CHIP_SDK_INVALID_PARAM)
+        ));
+        assert!(matches!(
+            SimSdk::with_topology(CHIP_SDK_CHIP_MAX + 1, 1, 10000),
+            Err(ChipSdkError::CHIP_SDK_NO_RESOURCE)
+        ));
+    }
+
+    #[test]
+    fn test_link_subscriptions_are_per_device() {
+        let (first_sim, first) = activated(1, 2, 10000);
+        let (second_sim, second) = activated(1, 2, 10000);
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let events = Arc::clone(&received);
+        let subscription = first
+            .subscribe_link_status(Box::new(move |port, status| {
+                events.lock().unwrap().push((port, status));
+            }))
+            .expect("Failed to subscribe");
+        let second_events = second.link_events(4).expect("Failed to subscribe");
+
+        first_sim
+            .set_link_status(&PhyPortId(0, 1), LinkStatus::LINK_UP)
+            .expect("Failed to set link status");
+        first_sim
+            .set_link_status(&PhyPortId(0, 1), LinkStatus::LINK_UP)
+            .expect("Failed to set link status");
+        assert_eq!(
+            *received.lock().unwrap(),
+            vec![(PhyPortId(0, 1), LinkStatus::LINK_UP)]
+        );
+        assert!(second_events.try_recv().is_none());
+
+        second_sim
+            .set_link_status(&PhyPortId(0, 0), LinkStatus::LINK_UP)
+            .expect("Failed to set link status");
+        let event = second_events.try_recv().expect("Missing link event");
+        assert_eq!(event.port, PhyPortId(0, 0));
+        assert_eq!(received.lock().unwrap().len(), 1);
+
+        assert_eq!(
+            first.port(&PhyPortId(0, 1)).unwrap().status,
+            LinkStatus::LINK_UP
+        );
+        assert_eq!(
+            first.port(&PhyPortId(0, 0)).unwrap().status,
+            LinkStatus::LINK_DOWN
+        );
+
+        subscription.unsubscribe();
+        first_sim
+            .set_link_status(&PhyPortId(0, 1), LinkStatus::LINK_DOWN)
+            .expect("Failed to set link status");
+        assert_eq!(received.lock().unwrap().len(), 1);
+        assert_eq!(
+            first.port(&PhyPortId(0, 1)).unwrap().status,
+            LinkStatus::LINK_DOWN
+        );
+        assert_eq!(
+            first_sim.set_link_status(&PhyPortId(1, 0), LinkStatus::LINK_UP),
+            Err(ChipSdkError::CHIP_SDK_INVALID_PARAM)
+        );
+    }
+
+    #[test]
+    fn test_link_events_without_capacity() {
+        let (sim, device) = activated(1, 1, 10000);
+        let events = device.link_events(0).expect("Failed to subscribe");
+
+        sim.set_link_status(&PhyPortId(0, 0), LinkStatus::LINK_UP)
+            .expect("Failed to set link status");
+        sim.set_link_status(&PhyPortId(0, 0), LinkStatus::LINK_DOWN)
+            .expect("Failed to set link status");
+        let event = events.try_recv().expect("Missing link event");
+        assert_eq!(event.status, LinkStatus::LINK_UP);
+        assert_eq!(events.missed(), 1);
+
+        sim.set_link_status(&PhyPortId(0, 0), LinkStatus::LINK_UP)
+            .expect("Failed to set link status");
+        let event = events.try_recv().expect("Missing link event");
+        assert_eq!((event.status, event.missed), (LinkStatus::LINK_UP, 1));
+    }
+
+    #[test]
+    fn test_link_event_stream_ends_on_deactivate() {
+        let (sim, device) = activated(1, 1, 10000);
+        let mut events = device.link_events(1).expect("Failed to subscribe");
+
+        sim.set_link_status(&PhyPortId(0, 0), LinkStatus::LINK_UP)
+            .expect("Failed to set link status");
+        let event = next_event(&mut events).expect("Missing link event");
+        assert_eq!(event.status, LinkStatus::LINK_UP);
+
+        // The stream is pending when the device drops its sender. A scoped
+        // thread would unpark this one on exit and hide a missing wake.
+        let deactivating = device.clone();
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            deactivating
+                .deactivate()
+                .expect("Failed to deactivate device");
+        });
+        assert_eq!(next_event(&mut events), None);
+        handle.join().expect("Failed to join deactivating thread");
+    }
+
+    #[test]
+    fn test_set_mac() {
+        let (sim, device) = activated(1, 2, 10000);
+        let mac: MacAddr = "02:00:00:00:00:01".parse().unwrap();
+
+        device
+            .set_mac(&PhyPortId(0, 1), mac)
+            .expect("Failed to set mac");
+        assert_eq!(sim.mac(&PhyPortId(0, 1)).map(MacAddr::from), Some(mac));
+        assert_eq!(device.mac(&PhyPortId(0, 1)), Some(mac));
+
+        assert_eq!(
+            device.set_mac(&PhyPortId(0, 2), mac),
+            Err(DeviceError::Sdk(ChipSdkError::CHIP_SDK_INVALID_PARAM))
+        );
+        assert_eq!(device.mac(&PhyPortId(0, 2)), None);
+
+        let err = device
+            .set_mac(&PhyPortId(0, 0), MacAddr::BROADCAST)
+            .unwrap_err();
+        assert_eq!(err, DeviceError::InvalidMac(MacAddr::BROADCAST));
+        assert_eq!(err.to_string(), "ff:ff:ff:ff:ff:ff is not a unicast MAC");
+        assert!(sim.mac(&PhyPortId(0, 0)).is_none());
+    }
+
+    #[test]
+    fn test_create_group_with_mac() {
+        let (sim, device) = activated(2, 2, 10000);
+        let base: MacAddr = "02:00:00:00:10:00".parse().unwrap();
+        let mut pool = MacPool::new(base, 4).expect("Failed to create pool");
+        let mut lag = LagManager::new();
+        let members = [PhyPortId(0, 0), PhyPortId(1, 0)];
+
+        let mac = create_group_with_mac(&device, &mut pool, &mut lag, 1, &members)
+            .expect("Failed to create group");
+        assert_eq!(mac, base);
+        assert_eq!(lag.group(1).and_then(|group| group.mac()), Some(mac));
+        for port in members {
+            assert_eq!(sim.mac(&port).map(MacAddr::from), Some(mac));
+        }
+        assert_eq!(sim.mac(&PhyPortId(0, 1)).map(MacAddr::from), None);
+
+        assert!(matches!(
+            create_group_with_mac(&device, &mut pool, &mut lag, 2, &[PhyPortId(0, 0)]),
+            Err(MacPoolError::Lag(LagError::PortAlreadyAggregated(..)))
+        ));
+        assert!(lag.group(2).is_none());
+        assert_eq!(pool.available(), 3);
+    }
+}
@@ -0,0 +1,130 @@
+mod device;
+use device::*;
+use lac::ffi::*;
+use lac::lac::*;
+use std::time::Duration;
+
+fn telemetry(port_num: usize) -> (ManualClock, LinkTelemetry<ManualClock>, LagManager) {
+    let (_, device) = activated(1, port_num, 10000);
+    let clock = ManualClock::new();
+    let mut telemetry = LinkTelemetry::with_clock(clock.clone());
+    telemetry.sync_topology(&device.topology());
+    let mut lag = LagManager::new();
+    lag.sync_topology(&device.topology());
+    (clock, telemetry, lag)
+}
+
+fn link(
+    telemetry: &mut LinkTelemetry<ManualClock>,
+    lag: &mut LagManager,
+    port: PhyPortId,
+    status: LinkStatus,
+) {
+    lag.update_link_status(port, status);
+    telemetry.record_link(port, status);
+    telemetry.observe_groups(lag);
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_port_counters() {
+        let (clock, mut telemetry, mut lag) = telemetry(2);
+        let port = PhyPortId(0, 0);
+        let start = telemetry.snapshot().taken_at;
+
+        clock.advance(Duration::from_secs(5));
+        link(&mut telemetry, &mut lag, port, LinkStatus::LINK_UP);
+        clock.advance(Duration::from_secs(10));
+        link(&mut telemetry, &mut lag, port, LinkStatus::LINK_UP);
+        link(&mut telemetry, &mut lag, port, LinkStatus::LINK_DOWN);
+        clock.advance(Duration::from_secs(2));
+        link(&mut telemetry, &mut lag, port, LinkStatus::LINK_UP);
+        clock.advance(Duration::from_secs(3));
+
+        let snapshot = telemetry.snapshot();
+        let counters = snapshot.port(&port).expect("Missing port telemetry");
+        assert_eq!(counters.status, LinkStatus::LINK_UP);
+        assert_eq!(counters.up_transitions, 2);
+        assert_eq!(counters.down_transitions, 1);
+        assert_eq!(counters.flaps(), 3);
+        assert_eq!(counters.uptime, Duration::from_secs(13));
+        assert_eq!(counters.state_duration, Duration::from_secs(3));
+        assert_eq!(counters.last_change, Some(start + Duration::from_secs(17)));
+
+        let idle = snapshot
+            .port(&PhyPortId(0, 1))
+            .expect("Missing port telemetry");
+        assert_eq!(idle.flaps(), 0);
+        assert_eq!(idle.last_change, None);
+        assert_eq!(idle.uptime, Duration::ZERO);
+        assert_eq!(idle.state_duration, Duration::from_secs(20));
+    }
+
+    #[test]
+    fn test_group_counters() {
+        let (clock, mut telemetry, mut lag) = telemetry(3);
+        lag.create_group(1).expect("Failed to create group");
+        lag.set_min_links(1, 2).expect("Failed to set min links");
+        telemetry.observe_groups(&lag);
+        for port_id in 0..3 {
+            lag.add_member(1, PhyPortId(0, port_id))
+                .expect("Failed to add member");
+            link(
+                &mut telemetry,
+                &mut lag,
+                PhyPortId(0, port_id),
+                LinkStatus::LINK_UP,
+            );
+        }
+        let snapshot = telemetry.snapshot();
+        let group = snapshot.group(1).expect("Missing group telemetry");
+        assert_eq!(group.state, GroupState::Up);
+        assert_eq!(group.member_churn, 3);
+
+        clock.advance(Duration::from_secs(1));
+        link(
+            &mut telemetry,
+            &mut lag,
+            PhyPortId(0, 0),
+            LinkStatus::LINK_DOWN,
+        );
+        clock.advance(Duration::from_secs(4));
+        link(
+            &mut telemetry,
+            &mut lag,
+            PhyPortId(0, 1),
+            LinkStatus::LINK_DOWN,
+        );
+        clock.advance(Duration::from_secs(2));
+        link(
+            &mut telemetry,
+            &mut lag,
+            PhyPortId(0, 0),
+            LinkStatus::LINK_UP,
+        );
+        clock.advance(Duration::from_secs(6));
+        link(
+            &mut telemetry,
+            &mut lag,
+            PhyPortId(0, 1),
+            LinkStatus::LINK_UP,
+        );
+        clock.advance(Duration::from_secs(1));
+
+        let snapshot = telemetry.snapshot();
+        let group = snapshot.group(1).expect("Missing group telemetry");
+        assert_eq!(group.state, GroupState::Up);
+        assert_eq!(group.time_degraded, Duration::from_secs(10));
+        assert_eq!(group.state_duration, Duration::from_secs(1));
+        assert_eq!(group.member_churn, 7);
+        assert_eq!(group.active_members.len(), 3);
+
+        lag.delete_group(1).expect("Failed to delete group");
+        telemetry.observe_groups(&lag);
+        assert!(telemetry.snapshot().group(1).is_none());
+    }
+}
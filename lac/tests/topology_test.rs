@@ -0,0 +1,74 @@
+mod device;
+use device::*;
+use lac::ffi::*;
+use lac::lac::*;
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        let mut chips = [chip(0, &[0, 1], 25000), chip(3, &[4, 5, 6], 25000)];
+        chips[1].ports[2].status = LinkStatus::LINK_UP;
+        let topology = Topology::from_raw(&chips).expect("Failed to build topology");
+
+        let chip_ids: Vec<_> = topology.chips().map(|chip| chip.id()).collect();
+        assert_eq!(chip_ids, vec![0, 3]);
+        let port_ids: Vec<_> = topology.ports().map(|port| port.id).collect();
+        assert_eq!(
+            port_ids,
+            vec![
+                PhyPortId(0, 0),
+                PhyPortId(0, 1),
+                PhyPortId(3, 4),
+                PhyPortId(3, 5),
+                PhyPortId(3, 6)
+            ]
+        );
+
+        let port = topology.port(&PhyPortId(3, 6)).expect("Missing port");
+        assert_eq!(port.speed, PortSpeed::GBPS_25);
+        assert!(port.is_up());
+        assert!(!topology.contains(&PhyPortId(0, 4)));
+        assert!(topology.chip(1).is_none());
+        assert_eq!(PortSpeed::GBPS_25.to_string(), "25G");
+        assert_eq!(PortSpeed::MBPS_100.to_string(), "100M");
+    }
+
+    #[test]
+    fn test_validation() {
+        let mut too_many_ports = chip(0, &[0], 25000);
+        too_many_ports.numOfPorts = CHIP_SDK_PHY_PORT_PER_CHIP as i32 + 1;
+        assert_eq!(
+            Topology::from_raw(&[too_many_ports]),
+            Err(TopologyError::TooManyPorts(0, 9))
+        );
+        too_many_ports.numOfPorts = -1;
+        assert_eq!(
+            Topology::from_raw(&[too_many_ports]),
+            Err(TopologyError::TooManyPorts(0, -1))
+        );
+
+        assert_eq!(
+            Topology::from_raw(&[chip(0, &[0], 25000), chip(0, &[1], 25000)]),
+            Err(TopologyError::DuplicateChip(0))
+        );
+        assert_eq!(
+            Topology::from_raw(&[chip(1, &[2, 2], 25000)]),
+            Err(TopologyError::DuplicatePort(PhyPortId(1, 2)))
+        );
+
+        let mut negative = chip(0, &[0], 25000);
+        negative.ports[0].speed = -1;
+        assert_eq!(
+            Topology::from_raw(&[negative]),
+            Err(TopologyError::InvalidSpeed(PhyPortId(0, 0), -1))
+        );
+        assert_eq!(
+            Topology::from_raw(&[SwitchChip::default(); CHIP_SDK_CHIP_MAX + 1]),
+            Err(TopologyError::TooManyChips(CHIP_SDK_CHIP_MAX + 1))
+        );
+    }
+}
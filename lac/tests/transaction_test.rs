@@ -0,0 +1,202 @@
+mod device;
+use device::*;
+use lac::ffi::*;
+use lac::lac::*;
+use lac::sdk::*;
+
+fn ports() -> Vec<PhyPortId> {
+    (0..4).map(|port_id| PhyPortId(0, port_id)).collect()
+}
+
+fn sim_mac(sdk: &FaultySdk<SimSdk>, port: &PhyPortId) -> Option<MacAddr> {
+    sdk.inner().mac(port).map(MacAddr::from)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_failed_step_rolls_back() {
+        let sdk =
+            FaultySdk::new(SimSdk::with_topology(1, 4, 10000).expect("Failed to build simulator"));
+        let device = activated_on(&sdk);
+        for (index, port) in ports().iter().enumerate() {
+            device
+                .set_mac(port, mac(index as u8))
+                .expect("Failed to set MAC");
+        }
+        sdk.inject(
+            Fault::new(SdkOp::SetMac, ChipSdkError::CHIP_SDK_NO_RESOURCE).on_port(PhyPortId(0, 2)),
+        );
+
+        let mut transaction = device.transaction();
+        for port in ports() {
+            transaction.set_mac(port, mac(0x10));
+        }
+        assert_eq!(transaction.ops().len(), 4);
+        let err = transaction.commit().expect_err("Transaction did not fail");
+        assert_eq!(err.step, 2);
+        assert_eq!(err.op, DeviceOp::SetMac(PhyPortId(0, 2), mac(0x10)));
+        assert_eq!(
+            err.error,
+            DeviceError::Sdk(ChipSdkError::CHIP_SDK_NO_RESOURCE)
+        );
+        assert_eq!(
+            err.rollback.undone,
+            vec![
+                DeviceOp::SetMac(PhyPortId(0, 1), mac(1)),
+                DeviceOp::SetMac(PhyPortId(0, 0), mac(0)),
+            ]
+        );
+        assert!(err.rollback.is_complete());
+        for (index, port) in ports().iter().enumerate() {
+            assert_eq!(device.mac(port), Some(mac(index as u8)));
+            assert_eq!(sim_mac(&sdk, port), Some(mac(index as u8)));
+        }
+        assert!(err.to_string().contains("rolled back 2 steps"));
+
+        sdk.clear();
+        let mut transaction = device.transaction();
+        transaction
+            .set_mac(PhyPortId(0, 2), mac(0x20))
+            .set_mac(PhyPortId(0, 3), mac(0x21));
+        transaction.commit().expect("Failed to commit");
+        assert_eq!(device.mac(&PhyPortId(0, 3)), Some(mac(0x21)));
+    }
+
+    #[test]
+    fn test_incomplete_rollback_is_reported() {
+        let sdk =
+            FaultySdk::new(SimSdk::with_topology(1, 4, 10000).expect("Failed to build simulator"));
+        let device = activated_on(&sdk);
+        device
+            .set_mac(&PhyPortId(0, 0), mac(1))
+            .expect("Failed to set MAC");
+        // The second call on port 0/0 is the one undoing the transaction.
+        sdk.inject(
+            Fault::new(SdkOp::SetMac, ChipSdkError::CHIP_SDK_TIMEOUT)
+                .on_port(PhyPortId(0, 0))
+                .trigger(FaultTrigger::Call(2)),
+        );
+        sdk.inject(
+            Fault::new(SdkOp::SetMac, ChipSdkError::CHIP_SDK_NO_RESOURCE).on_port(PhyPortId(0, 3)),
+        );
+
+        let mut transaction = device.transaction();
+        for port in ports() {
+            transaction.set_mac(port, mac(0x10));
+        }
+        let err = transaction.commit().expect_err("Transaction did not fail");
+        assert_eq!(err.step, 3);
+        assert!(!err.rollback.is_complete());
+        assert!(err.rollback.undone.is_empty());
+        assert_eq!(
+            err.rollback.failed,
+            vec![(
+                DeviceOp::SetMac(PhyPortId(0, 0), mac(1)),
+                DeviceError::Sdk(ChipSdkError::CHIP_SDK_TIMEOUT)
+            )]
+        );
+        assert_eq!(
+            err.rollback.unrestorable,
+            vec![PhyPortId(0, 2), PhyPortId(0, 1)]
+        );
+        assert_eq!(device.mac(&PhyPortId(0, 0)), Some(mac(0x10)));
+        assert_eq!(device.mac(&PhyPortId(0, 3)), None);
+        for port in &err.rollback.unrestorable {
+            assert_eq!(device.mac(port), Some(mac(0x10)));
+            assert_eq!(sim_mac(&sdk, port), Some(mac(0x10)));
+        }
+        assert!(err
+            .to_string()
+            .contains("port 0/2 has no MAC to restore, port 0/1 has no MAC to restore"));
+    }
+
+    #[test]
+    fn test_lag_transaction_is_all_or_nothing() {
+        let mut lag = LagManager::new();
+        lag.create_group(1).expect("Failed to create group");
+        lag.add_member(1, PhyPortId(0, 0))
+            .expect("Failed to add member");
+
+        let mut transaction = lag.transaction();
+        transaction
+            .stage(LagOp::CreateGroup(2))
+            .stage(LagOp::AddMember(2, PhyPortId(0, 1)))
+            .stage(LagOp::SetMinLinks(2, 2))
+            .stage(LagOp::AddMember(2, PhyPortId(0, 0)));
+        assert_eq!(transaction.ops().len(), 4);
+        let err = transaction.commit().expect_err("Transaction did not fail");
+        assert_eq!(err.step, 3);
+        assert_eq!(err.op, LagOp::AddMember(2, PhyPortId(0, 0)));
+        assert_eq!(
+            err.error,
+            LagError::PortAlreadyAggregated(PhyPortId(0, 0), 1)
+        );
+        assert_eq!(
+            err.to_string(),
+            "Step 4 failed to add port 0/0 to group 2: \
+             Port 0/0 already belongs to group 1; no change was made"
+        );
+        assert!(lag.group(2).is_none());
+        assert_eq!(lag.group_of(&PhyPortId(0, 1)), None);
+
+        let mut transaction = lag.transaction();
+        transaction
+            .stage(LagOp::RemoveMember(1, PhyPortId(0, 0)))
+            .stage(LagOp::CreateGroup(2))
+            .stage(LagOp::AddMember(2, PhyPortId(0, 0)))
+            .stage(LagOp::SetGroupMac(2, Some(mac(0x30))));
+        transaction.commit().expect("Failed to commit");
+        assert_eq!(lag.group_of(&PhyPortId(0, 0)), Some(2));
+        assert_eq!(lag.group(2).and_then(|group| group.mac()), Some(mac(0x30)));
+        assert!(lag.group(1).expect("Missing group").members().is_empty());
+    }
+
+    #[test]
+    fn test_config_and_group_mac_are_atomic() {
+        let sdk =
+            FaultySdk::new(SimSdk::with_topology(1, 4, 10000).expect("Failed to build simulator"));
+        let device = activated_on(&sdk);
+        let mut lag = LagManager::new();
+        lag.sync_topology(&device.topology());
+        sdk.inject(
+            Fault::new(SdkOp::SetMac, ChipSdkError::CHIP_SDK_NO_RESOURCE).on_port(PhyPortId(0, 2)),
+        );
+
+        let mut group = GroupConfig::new(1);
+        group.members = ports();
+        group.mac = Some(mac(0x10));
+        let config = LacConfig {
+            groups: vec![group],
+            ..Default::default()
+        };
+        match config.apply(&device, &mut lag, false) {
+            Err(ConfigError::Transaction(err)) => assert_eq!(err.step, 2),
+            other => panic!("Unexpected result {:?}", other),
+        }
+        assert_eq!(lag.groups().count(), 0);
+
+        lag.create_group(2).expect("Failed to create group");
+        for port in ports() {
+            lag.add_member(2, port).expect("Failed to add member");
+        }
+        let mut pool = MacPool::new(mac(0x40), 4).expect("Failed to create pool");
+        match apply_group_mac(&device, &mut pool, lag.group_mut(2).expect("Missing group")) {
+            Err(MacPoolError::Transaction(err)) => {
+                assert_eq!(err.op.port(), PhyPortId(0, 2));
+            }
+            other => panic!("Unexpected result {:?}", other),
+        }
+        assert_eq!(pool.get(&MacOwner::Group(2)), None);
+        assert_eq!(pool.available(), 4);
+        assert_eq!(lag.group(2).and_then(|group| group.mac()), None);
+
+        sdk.clear();
+        let mac = apply_group_mac(&device, &mut pool, lag.group_mut(2).expect("Missing group"))
+            .expect("Failed to apply");
+        assert!(ports().iter().all(|port| device.mac(port) == Some(mac)));
+    }
+}